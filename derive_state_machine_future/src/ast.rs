@@ -1,10 +1,13 @@
 //! AST types for state machines and their states.
 
-use darling;
+use darling::{self, FromMetaItem};
+use errors;
 use phases;
 use syn;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::Deref;
 
 /// A description of a state machine: its various states, which is the start
 /// state, ready state, and error state.
@@ -22,6 +25,150 @@ pub struct StateMachine<P: phases::Phase> {
     #[darling(default)]
     pub derive: darling::util::IdentList,
 
+    /// Whether to generate a `Stream` that yields the items of `yields`
+    /// states, rather than a `Future`.
+    #[darling(default)]
+    pub stream: bool,
+
+    /// Whether to record which states are entered, for `coverage`.
+    #[darling(default)]
+    pub coverage: bool,
+
+    /// Whether the generated `Future` records, in debug builds, the state it
+    /// is stalled in and for how many consecutive polls.
+    #[darling(default)]
+    pub stall_info: bool,
+
+    /// Whether the generated `Future` also has a `step` method, for driving it
+    /// synchronously one transition at a time.
+    #[darling(default)]
+    pub sync: bool,
+
+    /// Whether to generate a local equivalent of `RentToOwn`, instead of using
+    /// the one from the `rent_to_own` crate.
+    #[darling(default)]
+    pub inline_rent_to_own: bool,
+
+    /// Whether to skip generating the `start` function, leaving construction
+    /// to the generated `From` impl for the start state.
+    #[darling(default)]
+    pub no_start_fn: bool,
+
+    /// Whether to reject transitions back to the start state.
+    #[darling(default)]
+    pub no_start_loops: bool,
+
+    /// Whether to warn about states that list both the ready and error states
+    /// in their `transitions`.
+    #[darling(default)]
+    pub warn_ready_and_error: bool,
+
+    /// Whether the generated `Future` records the ordered sequence of states
+    /// that it has entered.
+    #[darling(default)]
+    pub record_path: bool,
+
+    /// Whether the generated `Future` can be turned into a `Stream` of the
+    /// states that it enters.
+    #[darling(default)]
+    pub transition_events: bool,
+
+    /// Whether to generate the fieldless mirror of the states, named after the
+    /// description followed by `State`. Everything that uses it turns it on
+    /// too.
+    #[darling(default)]
+    pub state_enum: bool,
+
+    /// Whether the generated `Future` has a `current_state` method.
+    #[darling(default)]
+    pub current_state: bool,
+
+    /// Whether the description has a `TRANSITIONS` constant.
+    #[darling(default)]
+    pub transitions_const: bool,
+
+    /// Whether the description has a `transitions_from` function.
+    #[darling(default)]
+    pub transitions_from: bool,
+
+    /// Whether the description has an `all_states` function.
+    #[darling(default)]
+    pub all_states: bool,
+
+    /// Whether the description has a `MERMAID` constant.
+    #[darling(default)]
+    pub mermaid: bool,
+
+    /// Whether the generated `Future` has a `reset` method.
+    #[darling(default)]
+    pub reset: bool,
+
+    /// Whether the generated `Future` has an `into_boxed` method.
+    #[darling(default)]
+    pub into_boxed: bool,
+
+    /// Whether the generated `Future` has a `map_machine_err` method.
+    #[darling(default)]
+    pub map_machine_err: bool,
+
+    /// Whether the generated `Future` has a `poll_one` method.
+    #[darling(default)]
+    pub poll_one: bool,
+
+    /// Whether the generated `Future` has an `is_finished` method.
+    #[darling(default)]
+    pub is_finished: bool,
+
+    /// Extra attributes for the generated `Future` (or `Stream`) type.
+    #[darling(default)]
+    pub future_attr: FutureAttrs,
+
+    /// Whether to check at compile time that the generated `Future` is `Send`.
+    #[darling(default)]
+    pub assert_send: bool,
+
+    /// Whether to check at compile time that the generated `Future` is `Sync`.
+    #[darling(default)]
+    pub assert_sync: bool,
+
+    /// A module to put the generated items in, instead of next to the
+    /// description.
+    #[darling(default)]
+    pub module: Option<syn::Ident>,
+
+    /// A name for the generated `Future` (or `Stream`) type, instead of the
+    /// description's name followed by `Future` (or `Stream`).
+    #[darling(default)]
+    pub future: Option<syn::Ident>,
+
+    /// The most bytes that the generated `Future` may take up, checked at
+    /// compile time.
+    #[darling(default, with = "int_from_meta_item")]
+    pub max_size: Option<u64>,
+
+    /// An integer type to use as the `repr` of the generated mirror state enum,
+    /// whose discriminants then follow declaration order.
+    #[darling(default)]
+    pub repr: Option<syn::Ident>,
+
+    /// Bounds that the ready state's item must meet, for example `Send`.
+    #[darling(default, with = "bounds_from_meta_item")]
+    pub ready_bound: Vec<syn::TyParamBound>,
+
+    /// Supertrait bounds for the generated poll trait, for example `Send`.
+    #[darling(default, with = "bounds_from_meta_item")]
+    pub poll_trait_bound: Vec<syn::TyParamBound>,
+
+    /// The name of a poll trait method to call after every successful
+    /// transition.
+    #[darling(default)]
+    pub on_transition: Option<syn::Ident>,
+
+    /// The name of a poll trait method that validates the start state, and
+    /// whose presence generates a fallible `try_start`.
+    #[darling(default)]
+    pub validate_start: Option<syn::Ident>,
+
     /// Extra per-phase data.
     #[darling(default)]
     pub extra: P::StateMachineExtra,
@@ -48,15 +195,302 @@ pub struct State<P: phases::Phase> {
     #[darling(default)]
     pub error: bool,
 
+    /// Whether the state machine can also be started in this state, besides
+    /// the start state.
+    #[darling(default)]
+    pub entry: bool,
+
     /// The set of other states that this one can transition to.
     #[darling(default)]
-    pub transitions: darling::util::IdentList,
+    pub transitions: Transitions,
+
+    /// Whether this state can transition to the ready state, without having to
+    /// name it in `transitions`.
+    #[darling(default)]
+    pub finish: bool,
+
+    /// Whether this state's `After*` enum has a `Stay` variant, for staying in
+    /// this state and polling it again straight away.
+    #[darling(default)]
+    pub stay: bool,
+
+    /// Whether this state is stored behind a `Box` in the generated states
+    /// enum.
+    #[darling(default)]
+    pub boxed: bool,
+
+    /// Whether this state yields an item from a `stream` state machine.
+    #[darling(default)]
+    pub yields: bool,
+
+    /// The error type of this state's poll method, if it differs from the
+    /// state machine's error type. It must convert `Into` the latter.
+    #[darling(default, with = "ty_from_meta_item")]
+    pub error_type: Option<syn::Ty>,
+
+    /// Whether to call an `on_enter_` poll trait method whenever a transition
+    /// enters this state.
+    #[darling(default)]
+    pub on_enter: Option<OnEnter>,
+
+    /// How many times to poll this state again, straight away, when its poll
+    /// method fails without taking the state.
+    #[darling(default)]
+    pub repoll: Option<Repoll>,
+
+    /// Whether to call an `on_exit_` poll trait method when the generated
+    /// `Future` is dropped while in this state.
+    #[darling(default)]
+    pub on_exit: bool,
+
+    /// The name of this state's poll trait method, instead of `poll_` followed
+    /// by the state's name in snake case.
+    #[darling(default)]
+    pub poll_fn: Option<syn::Ident>,
 
     /// Any extra per-phase data.
     #[darling(default)]
     pub extra: P::StateExtra,
 }
 
+/// The states listed in a `transitions(...)` attribute.
+///
+/// Unlike a bare `IdentList`, this remembers whether the attribute was present
+/// at all, so that we can tell a forgotten `transitions` apart from one whose
+/// last target was deleted. Each target may also carry a label, written as
+/// `Retry = "rare"`, which is only used when drawing the state machine.
+#[derive(Debug, Default, Clone)]
+pub struct Transitions {
+    idents: darling::util::IdentList,
+
+    /// The labels given to some of the transitions, by target state.
+    labels: HashMap<syn::Ident, String>,
+
+    /// The states that were listed more than once, and so were ignored after
+    /// the first time.
+    pub duplicates: Vec<syn::Ident>,
+
+    /// Whether a `transitions(...)` attribute was present on the state, even
+    /// if it was empty.
+    pub declared: bool,
+}
+
+impl Transitions {
+    /// Add `ident` to the states that can be transitioned to, unless it is
+    /// already there.
+    pub fn add(&mut self, ident: syn::Ident) {
+        if !self.idents.contains(&ident) {
+            let mut idents = self.idents.to_vec();
+            idents.push(ident);
+            self.idents = idents.into();
+        }
+    }
+
+    /// Add `ident` as written in the attribute, remembering it as a duplicate
+    /// if it was already listed.
+    fn add_listed(&mut self, ident: &syn::Ident) {
+        if self.idents.contains(ident) {
+            if !self.duplicates.contains(ident) {
+                self.duplicates.push(ident.clone());
+            }
+        } else {
+            self.add(ident.clone());
+        }
+    }
+
+    /// The label given to the transition to `ident`, if any.
+    pub fn label(&self, ident: &syn::Ident) -> Option<&str> {
+        self.labels.get(ident).map(|label| label.as_str())
+    }
+}
+
+impl FromMetaItem for Transitions {
+    fn from_word() -> darling::Result<Self> {
+        Err(darling::Error::custom(format!(
+            "{}, found a bare `transitions`",
+            errors::TRANSITIONS_SHAPE
+        )))
+    }
+
+    fn from_value(_: &syn::Lit) -> darling::Result<Self> {
+        Err(darling::Error::custom(format!(
+            "{}, found `transitions = ...`",
+            errors::TRANSITIONS_SHAPE
+        )))
+    }
+
+    fn from_list(items: &[syn::NestedMetaItem]) -> darling::Result<Self> {
+        let mut transitions = Transitions {
+            declared: true,
+            ..Transitions::default()
+        };
+        for item in items {
+            match *item {
+                syn::NestedMetaItem::MetaItem(syn::MetaItem::Word(ref ident)) => {
+                    transitions.add_listed(ident);
+                }
+                syn::NestedMetaItem::MetaItem(syn::MetaItem::NameValue(
+                    ref ident,
+                    syn::Lit::Str(ref label, _),
+                )) => {
+                    transitions.add_listed(ident);
+                    transitions.labels.insert(ident.clone(), label.clone());
+                }
+                _ => {
+                    return Err(darling::Error::custom(
+                        "`transitions` takes state names, each optionally labeled with a \
+                         string, for example `transitions(Retry = \"rare\", Done)`",
+                    ))
+                }
+            }
+        }
+        Ok(transitions)
+    }
+}
+
+impl Deref for Transitions {
+    type Target = Vec<syn::Ident>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.idents
+    }
+}
+
+/// A state's `on_enter` hook, written as `on_enter`, or as `on_enter(fallible)`
+/// for a hook that can fail the state machine.
+#[derive(Debug, Default)]
+pub struct OnEnter {
+    pub fallible: bool,
+}
+
+impl FromMetaItem for OnEnter {
+    fn from_word() -> darling::Result<Self> {
+        Ok(OnEnter::default())
+    }
+
+    fn from_list(items: &[syn::NestedMetaItem]) -> darling::Result<Self> {
+        let mut on_enter = OnEnter::default();
+        for item in items {
+            match *item {
+                syn::NestedMetaItem::MetaItem(syn::MetaItem::Word(ref word))
+                    if word == "fallible" =>
+                {
+                    on_enter.fallible = true
+                }
+                _ => {
+                    return Err(darling::Error::custom(
+                        "`on_enter` only accepts `fallible`",
+                    ))
+                }
+            }
+        }
+        Ok(on_enter)
+    }
+}
+
+/// A state's `repoll(max = 3)` policy: how many times a failed poll is
+/// repeated, with no delay, before its error is handled as usual.
+#[derive(Debug, Default)]
+pub struct Repoll {
+    pub max: u32,
+}
+
+impl FromMetaItem for Repoll {
+    fn from_list(items: &[syn::NestedMetaItem]) -> darling::Result<Self> {
+        let mut max = None;
+        for item in items {
+            match *item {
+                syn::NestedMetaItem::MetaItem(syn::MetaItem::NameValue(ref name, ref value))
+                    if name == "max" =>
+                {
+                    let value = int_from_lit(value)?;
+                    if value > u64::from(u32::MAX) {
+                        return Err(darling::Error::custom("`repoll` has too large a `max`"));
+                    }
+                    max = Some(value as u32);
+                }
+                syn::NestedMetaItem::MetaItem(syn::MetaItem::NameValue(ref name, _))
+                    if name == "backoff_ms" =>
+                {
+                    return Err(darling::Error::custom(
+                        "`repoll` does not support `backoff_ms`: futures 0.1 has no timer, \
+                         so it polls again straight away; to back off, store a timer \
+                         future in the state and return `NotReady` until it fires",
+                    ))
+                }
+                _ => return Err(darling::Error::custom("`repoll` only accepts `max = N`")),
+            }
+        }
+        match max {
+            Some(max) => Ok(Repoll { max }),
+            None => Err(darling::Error::custom("`repoll` requires `max = N`")),
+        }
+    }
+}
+
+/// The attributes in `future_attr(...)`. Each one is either a meta item, like
+/// `derive(Default)`, or a string holding the source of an attribute that we
+/// cannot parse, like `"allow(clippy::large_enum_variant)"`.
+#[derive(Debug, Default)]
+pub struct FutureAttrs(pub Vec<syn::NestedMetaItem>);
+
+impl FromMetaItem for FutureAttrs {
+    fn from_list(items: &[syn::NestedMetaItem]) -> darling::Result<Self> {
+        for item in items {
+            if let syn::NestedMetaItem::Literal(ref lit) = *item {
+                match *lit {
+                    syn::Lit::Str(..) => {}
+                    _ => {
+                        return Err(darling::Error::custom(
+                            "`future_attr` takes attributes, or strings of attribute source",
+                        ))
+                    }
+                }
+            }
+        }
+        Ok(FutureAttrs(items.to_vec()))
+    }
+}
+
+/// Parse a type out of a string, for example `error_type = "io::Error"`.
+fn ty_from_meta_item(item: &syn::MetaItem) -> darling::Result<Option<syn::Ty>> {
+    let ty = String::from_meta_item(item)?;
+    syn::parse_type(&ty)
+        .map(Some)
+        .map_err(darling::Error::custom)
+}
+
+/// Parse `+`-separated trait and lifetime bounds out of a string, for example
+/// `poll_trait_bound = "Send + 'static"`.
+fn bounds_from_meta_item(item: &syn::MetaItem) -> darling::Result<Vec<syn::TyParamBound>> {
+    let bounds = String::from_meta_item(item)?;
+    let clause = syn::parse_where_clause(&format!("where Self: {}", bounds))
+        .map_err(darling::Error::custom)?;
+    match clause.predicates.into_iter().next() {
+        Some(syn::WherePredicate::BoundPredicate(predicate)) => Ok(predicate.bounds),
+        _ => Err(darling::Error::custom(format!(
+            "`{}` is not a list of trait bounds",
+            bounds
+        ))),
+    }
+}
+
+/// Parse an integer attribute value, written either as a number or, since
+/// that is all that `darling` accepts, as a string.
+fn int_from_lit(value: &syn::Lit) -> darling::Result<u64> {
+    match *value {
+        syn::Lit::Int(value, _) => Ok(value),
+        _ => u64::from_value(value),
+    }
+}
+
+fn int_from_meta_item(item: &syn::MetaItem) -> darling::Result<Option<u64>> {
+    match *item {
+        syn::MetaItem::NameValue(_, ref value) => int_from_lit(value).map(Some),
+        _ => Err(darling::Error::unsupported_format("non-value")),
+    }
+}
+
 impl<P> StateMachine<P>
 where
     P: phases::Phase,
@@ -91,6 +525,38 @@ where
             body: darling::ast::Body::Enum(vec![]),
             attrs: self.attrs,
             derive: self.derive,
+            stream: self.stream,
+            coverage: self.coverage,
+            inline_rent_to_own: self.inline_rent_to_own,
+            sync: self.sync,
+            record_path: self.record_path,
+            transition_events: self.transition_events,
+            state_enum: self.state_enum,
+            current_state: self.current_state,
+            transitions_const: self.transitions_const,
+            transitions_from: self.transitions_from,
+            all_states: self.all_states,
+            mermaid: self.mermaid,
+            reset: self.reset,
+            into_boxed: self.into_boxed,
+            map_machine_err: self.map_machine_err,
+            poll_one: self.poll_one,
+            is_finished: self.is_finished,
+            no_start_loops: self.no_start_loops,
+            warn_ready_and_error: self.warn_ready_and_error,
+            no_start_fn: self.no_start_fn,
+            stall_info: self.stall_info,
+            assert_send: self.assert_send,
+            assert_sync: self.assert_sync,
+            future: self.future,
+            module: self.module,
+            future_attr: self.future_attr,
+            repr: self.repr,
+            max_size: self.max_size,
+            poll_trait_bound: self.poll_trait_bound,
+            ready_bound: self.ready_bound,
+            on_transition: self.on_transition,
+            validate_start: self.validate_start,
             extra: (),
         };
         (machine, extra, states)
@@ -103,6 +569,35 @@ where
             darling::ast::Body::Struct(_) => unreachable!(),
         }
     }
+
+    /// Whether to generate the mirror state enum, because it was asked for or
+    /// because something that uses it was.
+    pub fn has_state_enum(&self) -> bool {
+        self.state_enum
+            || self.current_state
+            || self.transitions_from
+            || self.all_states
+            || self.stall_info
+            || self.record_path
+            || self.transition_events
+            || self.repr.is_some()
+            || self.on_transition.is_some()
+    }
+}
+
+/// Renders the parsed state machine compactly, one state per line with its
+/// flags and transitions, for inclusion in error messages.
+impl<P> fmt::Display for StateMachine<P>
+where
+    P: phases::Phase,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "enum {} {{", self.ident)?;
+        for state in self.states() {
+            writeln!(f, "    {}", state)?;
+        }
+        write!(f, "}}")
+    }
 }
 
 impl StateMachine<phases::NoPhase> {
@@ -119,6 +614,38 @@ impl StateMachine<phases::NoPhase> {
             body: darling::ast::Body::Enum(states),
             attrs: self.attrs,
             derive: self.derive,
+            stream: self.stream,
+            coverage: self.coverage,
+            inline_rent_to_own: self.inline_rent_to_own,
+            sync: self.sync,
+            record_path: self.record_path,
+            transition_events: self.transition_events,
+            state_enum: self.state_enum,
+            current_state: self.current_state,
+            transitions_const: self.transitions_const,
+            transitions_from: self.transitions_from,
+            all_states: self.all_states,
+            mermaid: self.mermaid,
+            reset: self.reset,
+            into_boxed: self.into_boxed,
+            map_machine_err: self.map_machine_err,
+            poll_one: self.poll_one,
+            is_finished: self.is_finished,
+            no_start_loops: self.no_start_loops,
+            warn_ready_and_error: self.warn_ready_and_error,
+            no_start_fn: self.no_start_fn,
+            stall_info: self.stall_info,
+            assert_send: self.assert_send,
+            assert_sync: self.assert_sync,
+            future: self.future,
+            module: self.module,
+            future_attr: self.future_attr,
+            repr: self.repr,
+            max_size: self.max_size,
+            poll_trait_bound: self.poll_trait_bound,
+            ready_bound: self.ready_bound,
+            on_transition: self.on_transition,
+            validate_start: self.validate_start,
             extra,
         }
     }
@@ -149,13 +676,54 @@ where
             start: self.start,
             ready: self.ready,
             error: self.error,
+            entry: self.entry,
             transitions: self.transitions,
+            finish: self.finish,
+            boxed: self.boxed,
+            yields: self.yields,
+            error_type: self.error_type,
+            on_enter: self.on_enter,
+            poll_fn: self.poll_fn,
+            on_exit: self.on_exit,
+            repoll: self.repoll,
+            stay: self.stay,
             extra: (),
         };
         (state, extra)
     }
 }
 
+impl<P> fmt::Display for State<P>
+where
+    P: phases::Phase,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.ident)?;
+
+        let flags = [
+            (self.start, "start"),
+            (self.ready, "ready"),
+            (self.error, "error"),
+            (self.entry, "entry"),
+            (self.finish, "finish"),
+            (self.stay, "stay"),
+            (self.boxed, "boxed"),
+            (self.yields, "yields"),
+        ];
+        let flags: Vec<_> = flags.iter().filter(|f| f.0).map(|f| f.1).collect();
+        if !flags.is_empty() {
+            write!(f, " [{}]", flags.join(", "))?;
+        }
+
+        if !self.transitions.is_empty() {
+            let transitions: Vec<_> = self.transitions.iter().map(|t| t.as_ref()).collect();
+            write!(f, " -> {}", transitions.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
 pub trait CollectIdents {
     /// Collects idents that could be a type/lifetime parameter
     fn collect_idents(&self, idents: &mut HashSet<syn::Ident>);
@@ -165,7 +733,7 @@ impl CollectIdents for syn::Ty {
     fn collect_idents(&self, idents: &mut HashSet<syn::Ident>) {
         match *self {
             syn::Ty::Path(ref qself, ref p) => {
-                if let &Some(ref qself) = qself {
+                if let Some(qself) = qself {
                     qself.ty.collect_idents(idents);
                 }
 
@@ -174,7 +742,7 @@ impl CollectIdents for syn::Ty {
             syn::Ty::Slice(ref ty) | syn::Ty::Paren(ref ty) => ty.collect_idents(idents),
             syn::Ty::Ptr(ref ty) => ty.ty.collect_idents(idents),
             syn::Ty::Rptr(ref lifetime, ref ty) => {
-                if let &Some(ref lifetime) = lifetime {
+                if let Some(lifetime) = lifetime {
                     idents.insert(lifetime.ident.clone());
                 }
 
@@ -186,11 +754,45 @@ impl CollectIdents for syn::Ty {
                 ty.collect_idents(idents);
                 cexpr.collect_idents(idents);
             }
-            syn::Ty::Never
-            | syn::Ty::Mac(_)
-            | syn::Ty::TraitObject(_)
-            | syn::Ty::ImplTrait(_)
-            | syn::Ty::Infer => {}
+            syn::Ty::TraitObject(ref bounds) | syn::Ty::ImplTrait(ref bounds) => {
+                bounds.iter().for_each(|b| b.collect_idents(idents))
+            }
+            syn::Ty::Mac(ref mac) => mac.collect_idents(idents),
+            syn::Ty::Never | syn::Ty::Infer => {}
+        }
+    }
+}
+
+/// We can't know what a macro does with its input, but an argument that parses
+/// as a type is most likely used as one. Only the idents of those arguments are
+/// collected, so that a parameter's name that the macro takes as a value, such
+/// as a label, is not mistaken for a use of the parameter.
+impl CollectIdents for syn::Mac {
+    fn collect_idents(&self, idents: &mut HashSet<syn::Ident>) {
+        collect_type_args(&self.tts, idents)
+    }
+}
+
+/// Collect the idents of the comma or semicolon separated arguments inside the
+/// groups in `tts` that parse as types, looking further into the groups of the
+/// arguments that don't.
+fn collect_type_args(tts: &[syn::TokenTree], idents: &mut HashSet<syn::Ident>) {
+    for tt in tts {
+        if let syn::TokenTree::Delimited(ref delimited) = *tt {
+            let args = delimited.tts.split(|tt| {
+                matches!(
+                    *tt,
+                    syn::TokenTree::Token(syn::Token::Comma)
+                        | syn::TokenTree::Token(syn::Token::Semi)
+                )
+            });
+            for arg in args {
+                let tokens = quote!( #( #arg )* );
+                match syn::parse_type(tokens.as_str()) {
+                    Ok(ty) => ty.collect_idents(idents),
+                    Err(_) => collect_type_args(arg, idents),
+                }
+            }
         }
     }
 }
@@ -213,7 +815,15 @@ impl CollectIdents for syn::ConstExpr {
                 ty.collect_idents(idents);
                 c.collect_idents(idents);
             }
-            syn::ConstExpr::Path(ref p) => p.collect_idents(idents),
+            syn::ConstExpr::Path(ref p) => {
+                // An associated const such as `T::SIZE` still needs `T`.
+                if !p.global {
+                    if let Some(first) = p.segments.first() {
+                        idents.insert(first.ident.clone());
+                    }
+                }
+                p.collect_idents(idents)
+            }
             syn::ConstExpr::Lit(_) | syn::ConstExpr::Other(_) => {}
         }
     }
@@ -235,7 +845,7 @@ impl CollectIdents for syn::Path {
         // If the path contains only one segment and is not a global path,
         // it could be a generic type parameter, so we add the ident.
         if self.segments.len() == 1 && !self.global {
-            let last = self.segments.get(0).unwrap();
+            let last = self.segments.first().unwrap();
             idents.insert(last.ident.clone());
         }
 
@@ -348,8 +958,96 @@ impl State<phases::NoPhase> {
             start: self.start,
             ready: self.ready,
             error: self.error,
+            entry: self.entry,
             transitions: self.transitions,
+            finish: self.finish,
+            boxed: self.boxed,
+            yields: self.yields,
+            error_type: self.error_type,
+            on_enter: self.on_enter,
+            poll_fn: self.poll_fn,
+            on_exit: self.on_exit,
+            repoll: self.repoll,
+            stay: self.stay,
             extra,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::StateMachine;
+    use darling::FromDeriveInput;
+    use phases;
+    use syn;
+
+    #[test]
+    fn display_lists_states_with_flags() {
+        let input = syn::parse_derive_input(
+            r#"
+            pub enum Machine {
+                #[state_machine_future(start, transitions(Middle, Failed))]
+                Begin(()),
+
+                #[state_machine_future(boxed, finish)]
+                Middle(()),
+
+                #[state_machine_future(ready)]
+                Done(()),
+
+                #[state_machine_future(error)]
+                Failed(()),
+            }
+            "#,
+        ).unwrap();
+        let machine = StateMachine::<phases::NoPhase>::from_derive_input(&input).unwrap();
+
+        assert_eq!(
+            machine.to_string(),
+            "enum Machine {\n    \
+             Begin [start] -> Middle, Failed\n    \
+             Middle [finish, boxed]\n    \
+             Done [ready]\n    \
+             Failed [error]\n\
+             }"
+        );
+    }
+
+    #[test]
+    fn start_and_transitions_merge() {
+        for body in &[
+            "#[state_machine_future(start, transitions(Next))]",
+            "#[state_machine_future(start)] #[state_machine_future(transitions(Next))]",
+        ] {
+            let input = syn::parse_derive_input(&format!(
+                "enum Machine {{ {} Begin, #[state_machine_future(ready)] Next }}",
+                body
+            )).unwrap();
+            let machine = StateMachine::<phases::NoPhase>::from_derive_input(&input).unwrap();
+
+            let begin = &machine.states()[0];
+            assert!(begin.start, "{}", body);
+            assert_eq!(*begin.transitions, vec![syn::Ident::new("Next")], "{}", body);
+        }
+    }
+
+    #[test]
+    fn transitions_without_a_list_is_rejected() {
+        for attr in &["transitions", "transitions = \"Next\""] {
+            let input = syn::parse_derive_input(&format!(
+                "enum Machine {{ #[state_machine_future(start, {})] Begin, \
+                 #[state_machine_future(ready)] Next }}",
+                attr
+            )).unwrap();
+            let error = StateMachine::<phases::NoPhase>::from_derive_input(&input)
+                .unwrap_err()
+                .to_string();
+            assert!(
+                error.starts_with("expected `transitions(State1, State2)`"),
+                "{}: {}",
+                attr,
+                error
+            );
+        }
+    }
+}
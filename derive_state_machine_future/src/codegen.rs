@@ -38,13 +38,23 @@ fn to_var<S: AsRef<str>>(s: S) -> quote::Ident {
     }
 }
 
+// Wrap a typestate value for storage in the states enum, boxing it if its state
+// is `boxed`.
+fn box_if<T: ToTokens>(boxed: bool, value: T) -> quote::Tokens {
+    if boxed {
+        quote! { ::std::boxed::Box::new(#value) }
+    } else {
+        quote! { #value }
+    }
+}
+
 impl ToTokens for StateMachine<phases::ReadyForCodegen> {
     fn to_tokens(&self, tokens: &mut quote::Tokens) {
-        if cfg!(features = "debug_code_generation") {
+        if cfg!(feature = "debug_code_generation") {
             println!("StateMachine::to_tokens: self = {:#?}", self);
         }
 
-        let vis = &self.vis;
+        let vis = &*self.extra.vis;
         let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
         let states = self.states();
 
@@ -63,8 +73,14 @@ impl ToTokens for StateMachine<phases::ReadyForCodegen> {
                 let ty_generics = s.extra.generics.split_for_impl().1;
                 let ident = &s.ident;
 
-                quote! {
-                    #ident(#ident #ty_generics)
+                if s.boxed {
+                    quote! {
+                        #ident(::std::boxed::Box<#ident #ty_generics>)
+                    }
+                } else {
+                    quote! {
+                        #ident(#ident #ty_generics)
+                    }
                 }
             })
             .collect();
@@ -75,136 +91,1200 @@ impl ToTokens for StateMachine<phases::ReadyForCodegen> {
         let ready = &states[self.extra.ready];
         let future_item = &ready.data.fields[0];
 
-        let error = &states[self.extra.error];
-        let future_error = &error.data.fields[0];
-
-        let state_machine_attrs = &self.attrs;
+        let stream_item = states
+            .iter()
+            .find(|s| s.yields)
+            .map(|s| &s.data.fields[0].ty);
 
-        let state_machine_description_name = self.ident.to_string();
+        let future_error = &*self.extra.error_type;
 
-        let mut state_machine_name = state_machine_description_name.clone();
-        state_machine_name.push_str("Future");
+        let state_machine_attrs = &self.attrs;
+        let future_attrs: Vec<_> = self.future_attr
+            .0
+            .iter()
+            .map(|attr| match *attr {
+                syn::NestedMetaItem::MetaItem(ref meta) => quote! { #[#meta] },
+                syn::NestedMetaItem::Literal(ref lit) => {
+                    let mut tokens = quote! {};
+                    if let syn::Lit::Str(ref source, _) = *lit {
+                        tokens.append(format!("#[{}]", source));
+                    }
+                    tokens
+                }
+            })
+            .collect();
 
         let ident = &self.ident;
-        let state_machine_ident = quote::Ident::new(state_machine_name.as_str());
+        let state_machine_ident = &*self.extra.future;
+        let state_machine_name = state_machine_ident.as_ref();
         let states_enum = &*self.extra.states_enum;
 
-        let start_params = match start.data.style {
-            darling::ast::Style::Unit => vec![],
-            darling::ast::Style::Tuple => start
-                .data
-                .fields
-                .iter()
-                .cloned()
-                .enumerate()
-                .map(|(i, mut f)| {
-                    f.ident = Some(syn::Ident::new(format!("arg{}", i)));
-                    f
-                })
-                .collect(),
-            darling::ast::Style::Struct => start.data.fields.clone(),
+        let (start_params, start_value) = start.constructor();
+        let start_params = &start_params;
+
+        let poll_trait = &*self.extra.poll_trait;
+        let futures_crate = &*self.extra.futures_crate;
+
+        let state_kind = &*self.extra.state_kind;
+        let (stall_field, stall_init, stall_reset) = if self.stall_info {
+            (
+                quote! { , Option<(#state_kind, u32)> },
+                quote! { , None },
+                quote! { self.1 = None; },
+            )
+        } else {
+            (quote! {}, quote! {}, quote! {})
+        };
+        let (path_field, path_reset, path) = match self.extra.path_field {
+            Some(ref field) => {
+                let field = &**field;
+                let path_doc = doc_string(format!(
+                    "Every state that this `{}` has entered so far, in order, starting with \
+                     the state it was started in.",
+                    state_machine_ident
+                ));
+                (
+                    quote! { , ::std::vec::Vec<#state_kind> },
+                    quote! {
+                        self.#field.clear();
+                        self.#field.push(#state_kind::#start_state_ident);
+                    },
+                    quote! {
+                        #path_doc
+                        #[allow(dead_code)]
+                        #vis fn path(&self) -> &[#state_kind] {
+                            &self.#field
+                        }
+                    },
+                )
+            }
+            None => (quote! {}, quote! {}, quote! {}),
+        };
+        let (events_field, events_init, events_reset, transition_events) = match self
+            .extra
+            .events_field
+        {
+            Some(ref field) => {
+                let field = &**field;
+                let mut events = ident.as_ref().to_string();
+                events += "TransitionEvents";
+                let events = quote::Ident::new(events);
+                let method_doc = doc_string(format!(
+                    "Turn this `{}` into a `Stream` of the states that it enters, for \
+                     watching its progress live. Ends once the state machine is ready, or \
+                     with its error if it fails; its ready value is dropped.",
+                    state_machine_ident
+                ));
+                let struct_doc = doc_string(format!(
+                    "A `Stream` of the states that a `{}` enters, from its \
+                     `transition_events` method.",
+                    state_machine_ident
+                ));
+                (
+                    quote! { , Option<::std::vec::Vec<#state_kind>> },
+                    quote! { , None },
+                    quote! {
+                        if let Some(ref mut entered) = self.#field {
+                            entered.clear();
+                        }
+                    },
+                    quote! {
+                        impl #impl_generics #state_machine_ident #ty_generics #where_clause {
+                            #method_doc
+                            #[allow(dead_code)]
+                            #vis fn transition_events(mut self) -> #events #ty_generics {
+                                self.#field = Some(::std::vec::Vec::new());
+                                #events {
+                                    future: Some(self),
+                                    events: ::std::collections::VecDeque::new(),
+                                    error: None,
+                                }
+                            }
+                        }
+
+                        #struct_doc
+                        #[must_use = "streams do nothing unless polled"]
+                        #vis struct #events #impl_generics #where_clause {
+                            future: Option<#state_machine_ident #ty_generics>,
+                            events: ::std::collections::VecDeque<#state_kind>,
+                            error: Option<#future_error>,
+                        }
+
+                        impl #impl_generics #futures_crate::Stream
+                            for #events #ty_generics #where_clause {
+                            type Item = #state_kind;
+                            type Error = #future_error;
+
+                            fn poll(
+                                &mut self,
+                            ) -> #futures_crate::Poll<Option<Self::Item>, Self::Error> {
+                                loop {
+                                    if let Some(event) = self.events.pop_front() {
+                                        return Ok(#futures_crate::Async::Ready(Some(event)));
+                                    }
+                                    let result = match self.future {
+                                        Some(ref mut future) => {
+                                            let result = #futures_crate::Future::poll(future);
+                                            if let Some(ref mut entered) = future.#field {
+                                                self.events.extend(entered.drain(..));
+                                            }
+                                            result
+                                        }
+                                        // Only report the error once the events
+                                        // leading up to it have been taken.
+                                        None => {
+                                            return match self.error.take() {
+                                                Some(error) => Err(error),
+                                                None => Ok(#futures_crate::Async::Ready(None)),
+                                            };
+                                        }
+                                    };
+                                    match result {
+                                        Ok(#futures_crate::Async::NotReady) => {
+                                            if self.events.is_empty() {
+                                                return Ok(#futures_crate::Async::NotReady);
+                                            }
+                                        }
+                                        Ok(#futures_crate::Async::Ready(_)) => self.future = None,
+                                        Err(error) => {
+                                            self.future = None;
+                                            self.error = Some(error);
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    },
+                )
+            }
+            None => (quote! {}, quote! {}, quote! {}, quote! {}),
+        };
+        // The initial values of the generated `Future`'s optional fields, for a
+        // `Future` that starts in `state`.
+        let extra_fields = |state: &syn::Ident| {
+            let path_init = if self.record_path {
+                quote! { , vec![#state_kind::#state] }
+            } else {
+                quote! {}
+            };
+            quote! { #stall_init #path_init #events_init }
+        };
+        let start_extra_fields = extra_fields(start_state_ident);
+        // Record that a `Future` entered `state`, for `coverage`.
+        let cover_entry = |state: &syn::Ident| match self.extra.cover {
+            Some(ref cover) => {
+                let cover = &**cover;
+                let name = state.to_string();
+                quote! { #cover(#name); }
+            }
+            None => quote! {},
+        };
+        let cover_start = cover_entry(start_state_ident);
+
+        let (validate_start_method, try_start) = match self.validate_start {
+            Some(ref validate) => {
+                let (_, start_ty_generics, _) = start.extra.generics.split_for_impl();
+                let validate_doc = doc_string(format!(
+                    "Check that a `{}` state is a valid way to start the `{}` state machine, \
+                     before `try_start` constructs it.",
+                    start_state_ident, state_machine_name
+                ));
+                let try_start_doc = doc_string(format!(
+                    "Like `start`, but first checks the start state with `{}`, and returns \
+                     its error instead of a `{}` if the check fails.",
+                    validate, state_machine_name
+                ));
+                let boxed_start = box_if(start.boxed, quote! { start });
+                (
+                    quote! {
+                        #validate_doc
+                        fn #validate(start: &#start_state_ident #start_ty_generics)
+                            -> ::std::result::Result<(), #future_error>;
+                    },
+                    quote! {
+                        #try_start_doc
+                        #[allow(dead_code)]
+                        #vis fn try_start( #( #start_params ),* )
+                            -> ::std::result::Result<#state_machine_ident #ty_generics, #future_error>
+                        {
+                            let start = #start_value;
+                            <#ident #ty_generics as #poll_trait #ty_generics>::#validate(&start)?;
+                            #cover_start
+                            Ok(#state_machine_ident(
+                                Some(#states_enum::#start_state_ident(#boxed_start))
+                                #start_extra_fields
+                            ))
+                        }
+                    },
+                )
+            }
+            None => (quote! {}, quote! {}),
+        };
+
+        let start_value = box_if(start.boxed, start_value);
+
+        let poll_match_arms: Vec<_> = states
+            .iter()
+            .map(|state| state.future_poll_match_arm(&ty_generics))
+            .collect();
+
+        let poll_trait_methods: Vec<_> = states
+            .iter()
+            .filter(|s| !s.ready && !s.error && !s.yields)
+            .map(|state| state.poll_trait_method())
+            .collect();
+
+        let start_doc = doc_string(format!(
+            "Start executing the `{}` state machine. This constructing its `Future` \
+             representation in its initial start state and returns it.",
+            state_machine_name
+        ));
+
+        let start_at: Vec<_> = states
+            .iter()
+            .filter(|s| s.entry)
+            .map(|entry| {
+                let entry_ident = &entry.ident;
+                let (params, value) = entry.constructor();
+                let value = box_if(entry.boxed, value);
+                let entry_extra_fields = extra_fields(entry_ident);
+                let cover_entry = cover_entry(entry_ident);
+                let start_at = quote::Ident::new(format!(
+                    "start_at_{}",
+                    entry_ident.as_ref().to_snake_case()
+                ));
+                let doc = doc_string(format!(
+                    "Start executing the `{}` state machine in its `{}` entry state, instead \
+                     of its start state.",
+                    state_machine_name, entry_ident
+                ));
+                quote! {
+                    #doc
+                    #[allow(dead_code)]
+                    #vis fn #start_at( #( #params ),* ) -> #state_machine_ident #ty_generics {
+                        #cover_entry
+                        #state_machine_ident(
+                            Some(#states_enum::#entry_ident(#value))
+                            #entry_extra_fields
+                        )
+                    }
+                }
+            })
+            .collect();
+
+        let start_fn = if self.no_start_fn {
+            let (_, start_ty_generics, _) = start.extra.generics.split_for_impl();
+            let boxed_start = box_if(start.boxed, quote! { start });
+            quote! {
+                impl #impl_generics ::std::convert::From<#start_state_ident #start_ty_generics>
+                    for #state_machine_ident #ty_generics #where_clause
+                {
+                    fn from(start: #start_state_ident #start_ty_generics) -> Self {
+                        #cover_start
+                        #state_machine_ident(
+                            Some(#states_enum::#start_state_ident(#boxed_start))
+                            #start_extra_fields
+                        )
+                    }
+                }
+            }
+        } else {
+            quote! {
+                impl #impl_generics #ident #ty_generics #where_clause {
+                    #start_doc
+                    #[allow(dead_code)]
+                    #vis fn start( #( #start_params ),* ) -> #state_machine_ident #ty_generics {
+                        #cover_start
+                        #state_machine_ident(
+                            Some(
+                                #states_enum::#start_state_ident(
+                                    #start_value
+                                )
+                            )
+                            #start_extra_fields
+                        )
+                    }
+                }
+            }
+        };
+
+        let reset_doc = doc_string(format!(
+            "Drop whatever state this `{}` is in, after running its `on_exit` hook if it \
+             has one, and rewind it to a fresh start state built from the given arguments, \
+             as if it had just been returned by `start`.",
+            state_machine_name
+        ));
+
+        let smf_crate = &*self.extra.smf_crate;
+
+        let inline_rent_to_own = if self.inline_rent_to_own {
+            let rent_to_own = &*self.extra.rent_to_own;
+            let rent_to_own_doc = doc_string(format!(
+                "A local equivalent of `RentToOwn` for `{}`: derefs to the current state, \
+                 and lets its polling trait methods `take` ownership of it.",
+                ident
+            ));
+            quote! {
+                #rent_to_own_doc
+                #[derive(Debug)]
+                #vis struct #rent_to_own<'smf_rent, T: 'smf_rent> {
+                    inner: &'smf_rent mut Option<T>,
+                }
+
+                impl<'smf_rent, T> ::std::ops::Deref for #rent_to_own<'smf_rent, T> {
+                    type Target = T;
+                    fn deref(&self) -> &T {
+                        self.inner.as_ref().unwrap()
+                    }
+                }
+
+                impl<'smf_rent, T> ::std::ops::DerefMut for #rent_to_own<'smf_rent, T> {
+                    fn deref_mut(&mut self) -> &mut T {
+                        self.inner.as_mut().unwrap()
+                    }
+                }
+
+                impl<'smf_rent, T: 'smf_rent> #rent_to_own<'smf_rent, T> {
+                    #[allow(dead_code)]
+                    fn with<F, U>(inner: T, f: F) -> (Option<T>, U)
+                    where
+                        F: for<'smf_with> FnOnce(&'smf_with mut #rent_to_own<'smf_with, T>) -> U,
+                    {
+                        let mut inner = Some(inner);
+                        let u = {
+                            let mut me = #rent_to_own { inner: &mut inner };
+                            f(&mut me)
+                        };
+                        (inner, u)
+                    }
+
+                    /// Take ownership of the current state. The borrow lasts for the
+                    /// rest of this wrapper's life, so it cannot be used afterwards.
+                    #[allow(dead_code)]
+                    #vis fn take(&'smf_rent mut self) -> T {
+                        self.inner.take().unwrap()
+                    }
+                }
+            }
+        } else {
+            quote! {}
+        };
+
+        let coverage = match self.extra.cover {
+            Some(ref cover) => {
+                let cover = &**cover;
+                let mut coverage_fn = "__smf_coverage_for_".to_string();
+                coverage_fn += &ident.as_ref().to_snake_case();
+                let coverage_fn = quote::Ident::new(coverage_fn);
+
+                let state_names: Vec<_> = states.iter().map(|s| s.ident.to_string()).collect();
+                let state_names = &state_names;
+                let len = states.len();
+                let unvisited = states
+                    .iter()
+                    .map(|_| quote! { ::std::sync::atomic::AtomicBool::new(false) });
+                let coverage_doc = doc_string(format!(
+                    "The names of the `{}` states that any `{}` has entered since the \
+                     process started, or since `reset_coverage` was last called, in \
+                     declaration order.",
+                    ident, state_machine_name
+                ));
+                let reset_coverage_doc = doc_string(format!(
+                    "Forget which `{}` states have been entered, so that `coverage` only \
+                     reports the states entered from now on.",
+                    ident
+                ));
+                quote! {
+                    #[allow(dead_code)]
+                    fn #coverage_fn() -> &'static [::std::sync::atomic::AtomicBool] {
+                        static COVERAGE: [::std::sync::atomic::AtomicBool; #len] = [
+                            #( #unvisited ),*
+                        ];
+                        &COVERAGE
+                    }
+
+                    #[allow(dead_code)]
+                    fn #cover(state: &str) {
+                        let names: &[&'static str] = &[ #( #state_names ),* ];
+                        if let Some(index) = names.iter().position(|&name| name == state) {
+                            #coverage_fn()[index]
+                                .store(true, ::std::sync::atomic::Ordering::Relaxed);
+                        }
+                    }
+
+                    impl #impl_generics #ident #ty_generics #where_clause {
+                        #coverage_doc
+                        #[allow(dead_code)]
+                        #vis fn coverage() -> ::std::vec::Vec<&'static str> {
+                            let names: &[&'static str] = &[ #( #state_names ),* ];
+                            names
+                                .iter()
+                                .zip(#coverage_fn())
+                                .filter(|&(_, visited)| {
+                                    visited.load(::std::sync::atomic::Ordering::Relaxed)
+                                })
+                                .map(|(name, _)| *name)
+                                .collect()
+                        }
+
+                        #reset_coverage_doc
+                        #[allow(dead_code)]
+                        #vis fn reset_coverage() {
+                            for visited in #coverage_fn() {
+                                visited.store(false, ::std::sync::atomic::Ordering::Relaxed);
+                            }
+                        }
+                    }
+                }
+            }
+            None => quote! {},
+        };
+
+        // The dispatch loop, with `guard` run on each state before it is
+        // polled, so that `poll_one` and `step` can stop after a single poll.
+        let dispatch_loop = |guard: quote::Tokens| {
+            let poll_match_arms = &poll_match_arms;
+            quote! {
+                loop {
+                    let state = match self.0.take() {
+                        Some(state) => state,
+                        None => return Ok(#futures_crate::Async::NotReady),
+                    };
+                    #guard
+                    self.0 = match state {
+                        #( #poll_match_arms )*
+                    };
+                }
+            }
+        };
+        let poll_loop = dispatch_loop(quote! {});
+
+        let poll_one_loop = dispatch_loop(quote! {
+            if polled {
+                self.0 = Some(state);
+                return Ok(#futures_crate::Async::NotReady);
+            }
+            polled = true;
+        });
+        let poll_one_result = match stream_item {
+            Some(stream_item) => {
+                quote! { #futures_crate::Poll<Option<#stream_item>, #future_error> }
+            }
+            None => quote! { #futures_crate::Poll<#future_item, #future_error> },
+        };
+        let poll_one_doc = doc_string(format!(
+            "Poll this `{}`'s current state exactly once, instead of until it returns \
+             `NotReady`. Returns `NotReady` if the state returned `NotReady` or made a \
+             transition, so that tests can advance the state machine one state at a time.",
+            state_machine_name
+        ));
+        let poll_one = if self.poll_one {
+            quote! {
+                #poll_one_doc
+                #[allow(dead_code, unreachable_code)]
+                #vis fn poll_one(&mut self) -> #poll_one_result {
+                    let mut polled = false;
+                    #poll_one_loop
+                }
+            }
+        } else {
+            quote! {}
+        };
+
+        let step = if self.sync {
+            let finished: Vec<_> = states
+                .iter()
+                .filter(|s| s.ready || s.error)
+                .map(|s| {
+                    let s_ident = &s.ident;
+                    quote! { #states_enum::#s_ident(_) }
+                })
+                .collect();
+            let step_loop = dispatch_loop(quote! {
+                let finished = match state {
+                    #( #finished )|* => true,
+                    _ => false,
+                };
+                if stepped && !finished {
+                    self.0 = Some(state);
+                    return Ok(#futures_crate::Async::NotReady);
+                }
+                stepped = true;
+            });
+            let step_doc = doc_string(format!(
+                "Advance this `{}` by polling its current state once, without an executor. \
+                 Returns `StepResult::Done` once it reaches its ready or error state, and \
+                 `StepResult::Pending` otherwise, including when the state returned \
+                 `NotReady` or the state machine has already resolved.",
+                state_machine_name
+            ));
+            quote! {
+                #step_doc
+                #[allow(dead_code, unreachable_code)]
+                #vis fn step(&mut self) -> #smf_crate::StepResult<#future_item, #future_error> {
+                    let mut stepped = false;
+                    let result = (|| -> #futures_crate::Poll<#future_item, #future_error> {
+                        #step_loop
+                    })();
+                    match result {
+                        Ok(#futures_crate::Async::Ready(item)) => #smf_crate::StepResult::Done(Ok(item)),
+                        Ok(#futures_crate::Async::NotReady) => #smf_crate::StepResult::Pending,
+                        Err(error) => #smf_crate::StepResult::Done(Err(error)),
+                    }
+                }
+            }
+        } else {
+            quote! {}
+        };
+
+        // The `state_kind` of a `state: &#states_enum`.
+        let state_kind_of = {
+            let kinds = states.iter().map(|s| {
+                let s_ident = &s.ident;
+                quote! { #states_enum::#s_ident(_) => #state_kind::#s_ident, }
+            });
+            quote! {
+                match *state {
+                    #( #kinds )*
+                }
+            }
+        };
+        let current_state_doc = doc_string(format!(
+            "The state that this `{}` is currently in, or `None` if it has already \
+             resolved.",
+            state_machine_name
+        ));
+        let map_machine_err_doc = doc_string(format!(
+            "Convert this `{}`'s errors with `f`, keeping its item type.",
+            state_machine_name
+        ));
+        let map_machine_err = match stream_item {
+            _ if !self.map_machine_err => quote! {},
+            Some(_) => quote! {
+                #map_machine_err_doc
+                #[allow(dead_code)]
+                #vis fn map_machine_err<F, E2>(self, f: F) -> #futures_crate::stream::MapErr<Self, F>
+                where
+                    F: FnMut(#future_error) -> E2,
+                {
+                    #futures_crate::Stream::map_err(self, f)
+                }
+            },
+            None => quote! {
+                #map_machine_err_doc
+                #[allow(dead_code)]
+                #vis fn map_machine_err<F, E2>(self, f: F) -> #futures_crate::future::MapErr<Self, F>
+                where
+                    F: FnOnce(#future_error) -> E2,
+                {
+                    #futures_crate::Future::map_err(self, f)
+                }
+            },
+        };
+
+        let current_state = if self.current_state {
+            quote! {
+                #current_state_doc
+                #[allow(dead_code)]
+                #vis fn current_state(&self) -> Option<#state_kind> {
+                    self.0.as_ref().map(|state| #state_kind_of)
+                }
+            }
+        } else {
+            quote! {}
+        };
+
+        let poll_result = match stream_item {
+            Some(_) => quote! { #futures_crate::Poll<Option<Self::Item>, Self::Error> },
+            None => quote! { #futures_crate::Poll<Self::Item, Self::Error> },
+        };
+        let (poll_body, stall_info) = if self.stall_info {
+            let stall_info_doc = doc_string(format!(
+                "In debug builds, the state that this `{}` was in the last time it returned \
+                 `NotReady`, along with how many consecutive polls have returned \
+                 `NotReady` in that state. `None` if the last poll made it ready, failed, or \
+                 left a different state, and always `None` in release builds.",
+                state_machine_name
+            ));
+            (
+                quote! {
+                    let result = (|| -> #poll_result { #poll_loop })();
+                    if cfg!(debug_assertions) {
+                        self.1 = match (&result, &self.0) {
+                            (&Ok(#futures_crate::Async::NotReady), &Some(ref state)) => {
+                                let kind = #state_kind_of;
+                                match self.1 {
+                                    Some((stalled, polls)) if stalled == kind => {
+                                        Some((kind, polls.saturating_add(1)))
+                                    }
+                                    _ => Some((kind, 1)),
+                                }
+                            }
+                            _ => None,
+                        };
+                    }
+                    result
+                },
+                quote! {
+                    #stall_info_doc
+                    #[allow(dead_code)]
+                    #vis fn stall_info(&self) -> Option<(#state_kind, u32)> {
+                        self.1
+                    }
+                },
+            )
+        } else {
+            (poll_loop, quote! {})
+        };
+
+        // The size check is an associated const so that generic machines are
+        // checked for each instantiation that actually gets polled; machines
+        // without type parameters are also checked up front.
+        let poll_trait_bound = &self.poll_trait_bound;
+
+        let (poll_body, max_size) = match self.max_size {
+            Some(max_size) => {
+                let max_size = quote::Ident::new(max_size.to_string());
+                let message = format!(
+                    "`{}` is larger than its `max_size` of {} bytes",
+                    state_machine_ident, max_size
+                );
+                let eager_check = if self.generics.ty_params.is_empty() {
+                    quote! {
+                        const _: () = #state_machine_ident::__SMF_ASSERT_MAX_SIZE;
+                    }
+                } else {
+                    quote! {}
+                };
+                (
+                    quote! {
+                        let () = Self::__SMF_ASSERT_MAX_SIZE;
+                        #poll_body
+                    },
+                    quote! {
+                        impl #impl_generics #state_machine_ident #ty_generics #where_clause {
+                            #[allow(dead_code)]
+                            const __SMF_ASSERT_MAX_SIZE: () = assert!(
+                                ::std::mem::size_of::<Self>() <= #max_size,
+                                #message
+                            );
+                        }
+
+                        #eager_check
+                    },
+                )
+            }
+            None => (poll_body, quote! {}),
+        };
+
+        let boxed_trait = match stream_item {
+            Some(stream_item) => quote! {
+                #futures_crate::Stream<Item = #stream_item, Error = #future_error>
+            },
+            None => quote! {
+                #futures_crate::Future<Item = #future_item, Error = #future_error>
+            },
+        };
+        let boxed_send = if self.assert_send {
+            quote! { + Send }
+        } else {
+            quote! {}
+        };
+        let into_boxed_doc = doc_string(format!(
+            "Box this `{}` up as a trait object, so that it can be stored alongside \
+             other state machines with the same output.",
+            state_machine_name
+        ));
+
+        let (must_use, machine_impls, machine_trait) = match stream_item {
+            Some(stream_item) => (
+                quote! {
+                    #[must_use = "state machine streams do nothing unless polled"]
+                },
+                quote! {
+                    impl #impl_generics #futures_crate::Stream
+                        for #state_machine_ident #ty_generics #where_clause {
+                        type Item = #stream_item;
+                        type Error = #future_error;
+
+                        #[allow(unreachable_code)]
+                        fn poll(
+                            &mut self
+                        ) -> #futures_crate::Poll<Option<Self::Item>, Self::Error> {
+                            #poll_body
+                        }
+                    }
+
+                    impl #impl_generics #smf_crate::StateMachineStream
+                        for #ident #ty_generics #where_clause
+                    {
+                        type Stream = #state_machine_ident #ty_generics;
+                    }
+                },
+                quote! { #smf_crate::StateMachineStream },
+            ),
+            None => (
+                quote! {
+                    #[must_use = "state machine futures do nothing unless polled"]
+                },
+                quote! {
+                    impl #impl_generics #futures_crate::Future
+                        for #state_machine_ident #ty_generics #where_clause {
+                        type Item = #future_item;
+                        type Error = #future_error;
+
+                        #[allow(unreachable_code)]
+                        fn poll(&mut self) -> #futures_crate::Poll<Self::Item, Self::Error> {
+                            #poll_body
+                        }
+                    }
+
+                    impl #impl_generics #smf_crate::StateMachineFuture
+                        for #ident #ty_generics #where_clause
+                    {
+                        type Future = #state_machine_ident #ty_generics;
+                    }
+                },
+                quote! { #smf_crate::StateMachineFuture },
+            ),
+        };
+
+        // Each type that must implement some bound, with that bound.
+        let future_ty = quote! { #state_machine_ident #ty_generics };
+        let mut asserts = vec![];
+        if self.assert_send {
+            asserts.push((&future_ty, quote! { ::std::marker::Send }));
+        }
+        if self.assert_sync {
+            asserts.push((&future_ty, quote! { ::std::marker::Sync }));
+        }
+        let ready_item = quote! { #future_item };
+        if !self.ready_bound.is_empty() {
+            let ready_bound = &self.ready_bound;
+            asserts.push((&ready_item, quote! { #( #ready_bound )+* }));
+        }
+        let asserts = if asserts.is_empty() {
+            quote! {}
+        } else {
+            let mut assert = "__smf_assert_traits_for_".to_string();
+            assert += &ident.as_ref().to_snake_case();
+            let assert = quote::Ident::new(assert);
+            let checks = asserts.iter().map(|&(ty, ref bound)| {
+                quote! {
+                    {
+                        fn assert_impl<SmfAnyType: ?Sized + #bound>() {}
+                        assert_impl::<#ty>();
+                    }
+                }
+            });
+            quote! {
+                #[allow(dead_code)]
+                fn #assert #impl_generics () #where_clause {
+                    #( #checks )*
+                }
+            }
+        };
+
+        let finished_states: Vec<_> = states
+            .iter()
+            .filter(|s| s.ready || s.error)
+            .map(|s| {
+                let s_ident = &s.ident;
+                quote! { Some(#states_enum::#s_ident(_)) }
+            })
+            .collect();
+        let is_finished_doc = doc_string(format!(
+            "Whether this `{}` has reached its ready or error state, or has already \
+             resolved. Unlike polling, this never advances the state machine.",
+            state_machine_name
+        ));
+
+        let after_constructors: Vec<_> = states
+            .iter()
+            .filter(|s| !s.ready && !s.error)
+            .map(|s| s.after_constructors(states))
+            .collect();
+
+        let mut quiet = "__smf_quiet_warnings_for_".to_string();
+        quiet += &ident.as_ref().to_snake_case();
+        let quiet = quote::Ident::new(quiet);
+
+        let quiet_constructions: Vec<_> = self.states()
+            .iter()
+            .map(|s| {
+                let s_ident = &s.ident;
+                match s.data.style {
+                    darling::ast::Style::Unit => quote! {
+                        let _ = ::std::mem::replace(xxx, #ident::#s_ident);
+                    },
+                    darling::ast::Style::Tuple => {
+                        let fields = s.data.fields.iter().map(|_| {
+                            quote! {
+                                conjure()
+                            }
+                        });
+                        quote! {
+                            let _ = ::std::mem::replace(
+                                xxx,
+                                #ident::#s_ident( #( #fields ),* ),
+                            );
+                        }
+                    }
+                    darling::ast::Style::Struct => {
+                        let fields = s.data.fields.iter().map(|f| {
+                            let f = &f.ident;
+                            quote! {
+                                #f: conjure()
+                            }
+                        });
+
+                        let match_fields = s.data.fields.iter().map(|f| {
+                            let f = &f.ident;
+                            quote! {
+                                ref #f
+                            }
+                        });
+
+                        quote! {
+                            let _ = ::std::mem::replace(
+                                xxx,
+                                #ident::#s_ident { #( #fields ),* },
+                            );
+
+                            match *xxx {
+                                #ident::#s_ident { #( #match_fields ),* } => { unimplemented!() },
+                                _ => { unimplemented!() },
+                            }
+                        }
+                    }
+                }
+            })
+            .collect();
+
+        let state_kind_variants: Vec<_> = self.states().iter().map(|s| &s.ident).collect();
+        let (state_kind_repr, state_kind_variants) = match self.repr {
+            Some(ref repr) => {
+                let discriminants = (0..state_kind_variants.len())
+                    .map(|i| quote::Ident::new(i.to_string()))
+                    .collect::<Vec<_>>();
+                let variants = state_kind_variants
+                    .iter()
+                    .zip(discriminants)
+                    .map(|(v, d)| quote! { #v = #d })
+                    .collect::<Vec<_>>();
+                (quote! { #[repr(#repr)] }, variants)
+            }
+            None => (
+                quote! {},
+                state_kind_variants.iter().map(|v| quote! { #v }).collect(),
+            ),
+        };
+        let state_kind_names: Vec<_> = self
+            .states()
+            .iter()
+            .map(|s| {
+                let s_ident = &s.ident;
+                let name = s_ident.to_string();
+                quote! { #state_kind::#s_ident => #name, }
+            })
+            .collect();
+        let state_kind_doc = doc_string(format!(
+            "A fieldless mirror of the `{}` states, for observing a state machine's \
+             progress without borrowing its state.",
+            ident
+        ));
+
+        let state_kind_enum = if self.has_state_enum() {
+            quote! {
+                #state_kind_doc
+                #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+                #[allow(dead_code)]
+                #state_kind_repr
+                #vis enum #state_kind {
+                    #( #state_kind_variants ),*
+                }
+
+                impl ::std::fmt::Display for #state_kind {
+                    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                        f.pad(match *self {
+                            #( #state_kind_names )*
+                        })
+                    }
+                }
+            }
+        } else {
+            quote! {}
+        };
+
+        let on_transition_method = match self.on_transition {
+            Some(ref hook) => {
+                let hook_doc = doc_string(
+                    "Called after every successful transition, and on entering the error \
+                     state, with the state that was left and the state that was entered. \
+                     Does nothing by default. Takes no `self`, since the description is \
+                     never constructed.",
+                );
+                quote! {
+                    #hook_doc
+                    #[allow(unused_variables)]
+                    fn #hook(from: #state_kind, to: #state_kind) {}
+                }
+            }
+            None => quote! {},
+        };
+
+        let on_enter_methods: Vec<_> = self.states()
+            .iter()
+            .filter_map(|s| s.on_enter.as_ref().map(|on_enter| (s, on_enter)))
+            .map(|(s, on_enter)| {
+                let s_ident = &s.ident;
+                let (_, s_ty_generics, _) = s.extra.generics.split_for_impl();
+                let hook = quote::Ident::new(format!(
+                    "on_enter_{}",
+                    s_ident.as_ref().to_snake_case()
+                ));
+                let (doc, result) = if on_enter.fallible {
+                    (
+                        format!(
+                            "Called whenever a transition enters the `{}` state, before it \
+                             is polled. Returning an error fails the state machine, just \
+                             like returning one from a polling method.",
+                            s_ident
+                        ),
+                        quote! { -> ::std::result::Result<(), #future_error> },
+                    )
+                } else {
+                    (
+                        format!(
+                            "Called whenever a transition enters the `{}` state, before it \
+                             is polled.",
+                            s_ident
+                        ),
+                        quote! {},
+                    )
+                };
+                let doc = doc_string(doc);
+                quote! {
+                    #doc
+                    fn #hook(state: &mut #s_ident #s_ty_generics) #result;
+                }
+            })
+            .collect();
+
+        let on_exit_states: Vec<_> = self.states().iter().filter(|s| s.on_exit).collect();
+        let on_exit_methods: Vec<_> = on_exit_states
+            .iter()
+            .map(|s| {
+                let s_ident = &s.ident;
+                let (_, s_ty_generics, _) = s.extra.generics.split_for_impl();
+                let hook = quote::Ident::new(format!("on_exit_{}", s_ident.as_ref().to_snake_case()));
+                let doc = doc_string(format!(
+                    "Called when the state machine's `Future` is dropped or `reset` while it \
+                     is in the `{}` state, for example because it was cancelled. Not called \
+                     when the state is left by a transition.",
+                    s_ident
+                ));
+                quote! {
+                    #doc
+                    fn #hook(state: &mut #s_ident #s_ty_generics);
+                }
+            })
+            .collect();
+        // Run the current state's `on_exit` hook, if it has one, for when the
+        // `Future` is dropped or `reset`.
+        let exit_current_state = if on_exit_states.is_empty() {
+            quote! {}
+        } else {
+            let arms = on_exit_states.iter().map(|s| {
+                let s_ident = &s.ident;
+                let hook = quote::Ident::new(format!("on_exit_{}", s_ident.as_ref().to_snake_case()));
+                quote! {
+                    Some(#states_enum::#s_ident(ref mut state)) => {
+                        <#ident #ty_generics as #poll_trait #ty_generics>::#hook(state)
+                    }
+                }
+            });
+            quote! {
+                match self.0 {
+                    #( #arms )*
+                    _ => {}
+                }
+            }
+        };
+        // Only implement `Drop` when it is needed, since a type with a `Drop`
+        // impl cannot be moved out of.
+        let on_exit_drop = if on_exit_states.is_empty() {
+            quote! {}
+        } else {
+            quote! {
+                impl #impl_generics Drop for #state_machine_ident #ty_generics #where_clause {
+                    fn drop(&mut self) {
+                        #exit_current_state
+                    }
+                }
+            }
+        };
+
+        let mut mermaid = String::from("stateDiagram-v2\n");
+        mermaid.push_str(&format!("    [*] --> {}\n", start_state_ident));
+        for state in self.states() {
+            for target in state.transitions.iter() {
+                mermaid.push_str(&format!("    {} --> {}", state.ident, target));
+                if let Some(label) = state.transitions.label(target) {
+                    mermaid.push_str(&format!(": {}", label));
+                }
+                mermaid.push('\n');
+            }
+            if state.ready || state.error {
+                mermaid.push_str(&format!("    {} --> [*]\n", state.ident));
+            }
+        }
+        let mermaid_doc = doc_string(format!(
+            "A Mermaid `stateDiagram-v2` of `{}`, with an edge for every declared \
+             transition, labeled with its `transitions` label if it has one.",
+            ident
+        ));
+
+        let transitions: Vec<_> = self
+            .states()
+            .iter()
+            .map(|state| {
+                let name = state.ident.to_string();
+                let targets: Vec<_> = state.transitions.iter().map(|t| t.to_string()).collect();
+                quote! {
+                    (#name, &[ #( #targets ),* ])
+                }
+            })
+            .collect();
+        let transitions_doc = doc_string(format!(
+            "Each state of `{}` paired with the states it may transition to, in \
+             declaration order.",
+            ident
+        ));
+
+        let transitions_from = if self.transitions_from {
+            let arms = self.states().iter().map(|state| {
+                let state_ident = &state.ident;
+                let targets = state.transitions.iter();
+                let state_kinds = state.transitions.iter().map(|_| state_kind);
+                quote! {
+                    #state_kind::#state_ident => &[ #( #state_kinds::#targets ),* ],
+                }
+            });
+            let doc = doc_string(format!(
+                "The states that the given `{}` state may transition to, in declaration \
+                 order. This is the typed counterpart of `TRANSITIONS`.",
+                ident
+            ));
+            quote! {
+                #doc
+                #[allow(dead_code)]
+                #vis fn transitions_from(state: #state_kind) -> &'static [#state_kind] {
+                    match state {
+                        #( #arms )*
+                    }
+                }
+            }
+        } else {
+            quote! {}
+        };
+
+        let all_states = if self.all_states {
+            let states = self.states().iter().map(|state| &state.ident);
+            let state_kinds = self.states().iter().map(|_| state_kind);
+            let doc = doc_string(format!(
+                "Every state of `{}`, in declaration order.",
+                ident
+            ));
+            quote! {
+                #doc
+                #[allow(dead_code)]
+                #vis fn all_states() -> impl Iterator<Item = #state_kind> {
+                    const ALL_STATES: &[#state_kind] = &[ #( #state_kinds::#states ),* ];
+                    ALL_STATES.iter().cloned()
+                }
+            }
+        } else {
+            quote! {}
+        };
+
+        let transitions_const = if self.transitions_const {
+            quote! {
+                #transitions_doc
+                #[allow(dead_code)]
+                #vis const TRANSITIONS: &'static [(&'static str, &'static [&'static str])] = &[
+                    #( #transitions ),*
+                ];
+            }
+        } else {
+            quote! {}
+        };
+
+        let mermaid_const = if self.mermaid {
+            quote! {
+                #mermaid_doc
+                #[allow(dead_code)]
+                #vis const MERMAID: &'static str = #mermaid;
+            }
+        } else {
+            quote! {}
         };
-        let start_params = &start_params;
 
-        let start_value = match start.data.style {
-            darling::ast::Style::Unit => quote! {
-                #start_state_ident
-            },
-            darling::ast::Style::Tuple => {
-                let args = start_params.iter().map(|f| &f.ident);
-                quote! {
-                    #start_state_ident( #( #args ),* )
+        let reset = if self.reset {
+            quote! {
+                #reset_doc
+                #[allow(dead_code)]
+                #vis fn reset(&mut self, #( #start_params ),* ) {
+                    #exit_current_state
+                    self.0 = Some(#states_enum::#start_state_ident(#start_value));
+                    #stall_reset
+                    #path_reset
+                    #events_reset
+                    #cover_start
                 }
             }
-            darling::ast::Style::Struct => {
-                let args = start_params.iter().map(|f| &f.ident);
-                quote! {
-                    #start_state_ident { #( #args ),* }
+        } else {
+            quote! {}
+        };
+
+        let into_boxed = if self.into_boxed {
+            quote! {
+                #into_boxed_doc
+                #[allow(dead_code)]
+                #vis fn into_boxed<'__smf_boxed>(
+                    self
+                ) -> ::std::boxed::Box<dyn #boxed_trait #boxed_send + '__smf_boxed>
+                where
+                    Self: '__smf_boxed,
+                {
+                    ::std::boxed::Box::new(self)
                 }
             }
+        } else {
+            quote! {}
         };
 
-        let poll_match_arms: Vec<_> = states
-            .iter()
-            .map(|state| state.future_poll_match_arm(&ty_generics))
-            .collect();
-
-        let poll_trait = &*self.extra.poll_trait;
-        let poll_trait_methods: Vec<_> = states
-            .iter()
-            .filter(|s| !s.ready && !s.error)
-            .map(|state| state.poll_trait_method())
-            .collect();
-
-        let start_doc = doc_string(format!(
-            "Start executing the `{}` state machine. This constructing its `Future` \
-             representation in its initial start state and returns it.",
-            state_machine_name
-        ));
-
-        let futures_crate = &*self.extra.futures_crate;
-        let smf_crate = &*self.extra.smf_crate;
-
-        let mut quiet = "__smf_quiet_warnings_for_".to_string();
-        quiet += &state_machine_name.to_snake_case();
-        let quiet = quote::Ident::new(quiet);
-
-        let quiet_constructions: Vec<_> = self.states()
-            .iter()
-            .map(|s| {
-                let s_ident = &s.ident;
-                match s.data.style {
-                    darling::ast::Style::Unit => quote! {
-                        let _ = ::std::mem::replace(xxx, #ident::#s_ident);
-                    },
-                    darling::ast::Style::Tuple => {
-                        let fields = s.data.fields.iter().map(|_| {
-                            quote! {
-                                conjure()
-                            }
-                        });
-                        quote! {
-                            let _ = ::std::mem::replace(
-                                xxx,
-                                #ident::#s_ident( #( #fields ),* ),
-                            );
-                        }
-                    }
-                    darling::ast::Style::Struct => {
-                        let fields = s.data.fields.iter().map(|f| {
-                            let f = &f.ident;
-                            quote! {
-                                #f: conjure()
-                            }
-                        });
-
-                        let match_fields = s.data.fields.iter().map(|f| {
-                            let f = &f.ident;
-                            quote! {
-                                ref #f
-                            }
-                        });
-
-                        quote! {
-                            let _ = ::std::mem::replace(
-                                xxx,
-                                #ident::#s_ident { #( #fields ),* },
-                            );
-
-                            match *xxx {
-                                #ident::#s_ident { #( #match_fields ),* } => { unimplemented!() },
-                                _ => { unimplemented!() },
-                            }
-                        }
+        let is_finished = if self.is_finished {
+            quote! {
+                #is_finished_doc
+                #[allow(dead_code)]
+                #vis fn is_finished(&self) -> bool {
+                    match self.0 {
+                        None #( | #finished_states )* => true,
+                        _ => false,
                     }
                 }
-            })
-            .collect();
+            }
+        } else {
+            quote! {}
+        };
 
-        tokens.append(quote! {
+        let generated = quote! {
             extern crate futures as #futures_crate;
             extern crate state_machine_future as #smf_crate;
 
+            #inline_rent_to_own
+
             #( #states )*
 
             #derive
@@ -214,58 +1294,83 @@ impl ToTokens for StateMachine<phases::ReadyForCodegen> {
             }
 
             #( #state_machine_attrs )*
+            #( #future_attrs )*
             #derive
-            #[must_use = "futures do nothing unless polled"]
+            #must_use
             #vis struct #state_machine_ident #impl_generics(
                 Option<#states_enum #ty_generics>
+                #stall_field
+                #path_field
+                #events_field
             ) #where_clause;
 
-            impl #impl_generics #futures_crate::Future
-                for #state_machine_ident #ty_generics #where_clause {
-                type Item = #future_item;
-                type Error = #future_error;
-
-                #[allow(unreachable_code)]
-                fn poll(&mut self) -> #futures_crate::Poll<Self::Item, Self::Error> {
-                    loop {
-                        let state = match self.0.take() {
-                            Some(state) => state,
-                            None => return Ok(#futures_crate::Async::NotReady),
-                        };
-                        self.0 = match state {
-                            #( #poll_match_arms )*
-                        };
-                    }
-                }
-            }
+            #machine_impls
 
-            impl #impl_generics #smf_crate::StateMachineFuture
-                for #ident #ty_generics #where_clause
-            {
-                type Future = #state_machine_ident #ty_generics;
-            }
+            #state_kind_enum
 
             #vis trait #poll_trait #impl_generics
-                : #smf_crate::StateMachineFuture
+                : #machine_trait #( + #poll_trait_bound )*
                 #where_clause
             {
                 #( #poll_trait_methods )*
+
+                #on_transition_method
+
+                #( #on_enter_methods )*
+
+                #( #on_exit_methods )*
+
+                #validate_start_method
             }
 
             impl #impl_generics #ident #ty_generics #where_clause {
-                #start_doc
-                #[allow(dead_code)]
-                #vis fn start( #( #start_params ),* ) -> #state_machine_ident #ty_generics {
-                    #state_machine_ident(
-                        Some(
-                            #states_enum::#start_state_ident(
-                                #start_value
-                            )
-                        )
-                    )
-                }
+                #transitions_const
+
+                #transitions_from
+
+                #all_states
+
+                #mermaid_const
+
+                #( #start_at )*
+
+                #try_start
+            }
+
+            #start_fn
+
+            impl #impl_generics #state_machine_ident #ty_generics #where_clause {
+                #reset
+
+                #into_boxed
+
+                #current_state
+
+                #map_machine_err
+
+                #poll_one
+
+                #step
+
+                #stall_info
+
+                #path
+
+                #is_finished
             }
 
+            #( #after_constructors )*
+
+            #on_exit_drop
+
+            #coverage
+
+            #asserts
+
+            #max_size
+
+            #transition_events
+
             #[allow(warnings)]
             fn #quiet #impl_generics (xxx: &mut #ident #ty_generics) #where_clause {
                 fn conjure<SmfAnyType>() -> SmfAnyType {
@@ -275,7 +1380,27 @@ impl ToTokens for StateMachine<phases::ReadyForCodegen> {
                     #quiet_constructions;
                 )*
             }
-        });
+        };
+
+        match self.module {
+            Some(ref module) => {
+                // A glob re-export, so that the description's own items shadow
+                // the generated ones instead of clashing with them.
+                let outer_vis = &self.vis;
+                tokens.append(quote! {
+                    #outer_vis mod #module {
+                        #[allow(unused_imports)]
+                        use super::*;
+
+                        #generated
+                    }
+
+                    #[allow(unused_imports)]
+                    #outer_vis use self::#module::*;
+                });
+            }
+            None => tokens.append(generated),
+        }
 
         if cfg!(feature = "debug_code_generation") {
             use std::io::Write;
@@ -286,7 +1411,7 @@ impl ToTokens for StateMachine<phases::ReadyForCodegen> {
             println!();
 
             let mut child = process::Command::new("rustup")
-                .args(&["run", "nightly", "rustfmt"])
+                .args(["run", "nightly", "rustfmt"])
                 .stdin(process::Stdio::piped())
                 .spawn()
                 .unwrap_or_else(|_| {
@@ -309,6 +1434,78 @@ impl ToTokens for StateMachine<phases::ReadyForCodegen> {
 }
 
 impl State<phases::ReadyForCodegen> {
+    /// The parameters of a function that constructs this state, and the
+    /// expression that constructs it from those parameters.
+    fn constructor(&self) -> (Vec<syn::Field>, quote::Tokens) {
+        let ident = &self.ident;
+        match self.data.style {
+            darling::ast::Style::Unit => (vec![], quote! { #ident }),
+            darling::ast::Style::Tuple => {
+                let params: Vec<_> = self.data
+                    .fields
+                    .iter()
+                    .cloned()
+                    .enumerate()
+                    .map(|(i, mut f)| {
+                        f.ident = Some(syn::Ident::new(format!("arg{}", i)));
+                        f
+                    })
+                    .collect();
+                let value = {
+                    let args = params.iter().map(|f| &f.ident);
+                    quote! { #ident( #( #args ),* ) }
+                };
+                (params, value)
+            }
+            darling::ast::Style::Struct => {
+                let params = self.data.fields.clone();
+                let value = {
+                    let args = params.iter().map(|f| &f.ident);
+                    quote! { #ident { #( #args ),* } }
+                };
+                (params, value)
+            }
+        }
+    }
+
+    /// An `into_foo` constructor on this state's `After*` enum for each state
+    /// `Foo` that it can transition to.
+    fn after_constructors(&self, states: &[State<phases::ReadyForCodegen>]) -> quote::Tokens {
+        let vis = &*self.extra.vis;
+        let after_ident = &self.extra.after;
+        let (after_impl_generics, after_ty_generics, after_where_clause) =
+            self.extra.after_state_generics.split_for_impl();
+
+        let constructors = self.transitions.iter().map(|t| {
+            let target = states
+                .iter()
+                .find(|s| s.ident == *t)
+                .expect("transitions are to valid states");
+            let (params, value) = target.constructor();
+            let into = quote::Ident::new(format!("into_{}", t.as_ref().to_snake_case()));
+            let doc = doc_string(format!(
+                "Transition from the `{}` state to a new `{}` state.",
+                self.ident, t
+            ));
+            quote! {
+                #doc
+                #[allow(dead_code)]
+                #vis fn #into( #( #params ),* ) -> Self {
+                    #after_ident::#t(#value)
+                }
+            }
+        });
+
+        let allows = self.attrs.iter().filter(|a| a.name() == "allow");
+
+        quote! {
+            #( #allows )*
+            impl #after_impl_generics #after_ident #after_ty_generics #after_where_clause {
+                #( #constructors )*
+            }
+        }
+    }
+
     fn future_poll_match_arm(&self, ty_generics: &syn::TyGenerics) -> quote::Tokens {
         let ident = &self.ident;
         let ident_string = ident.to_string();
@@ -316,55 +1513,255 @@ impl State<phases::ReadyForCodegen> {
         let states_enum = &*self.extra.states_enum;
         let poll_trait = &*self.extra.poll_trait;
         let futures_crate = &*self.extra.futures_crate;
-        let smf_crate = &*self.extra.smf_crate;
+        let rent_to_own = &*self.extra.rent_to_own;
+
+        let state_value = if self.boxed {
+            quote! { *#var }
+        } else {
+            quote! { #var }
+        };
+
+        if self.ready && self.extra.stream {
+            // The stream is exhausted once it reaches the ready state.
+            return quote! {
+                #states_enum::#ident(_) => {
+                    return Ok(#futures_crate::Async::Ready(None));
+                }
+            };
+        }
 
         if self.ready {
             return quote! {
-                #states_enum::#ident(#ident(#var)) => {
+                #states_enum::#ident(#var) => {
+                    let #ident(#var) = #state_value;
                     return Ok(#futures_crate::Async::Ready(#var));
                 }
             };
         }
 
-        let error_ident = &*self.extra.error_ident;
-        let error_var = to_var(error_ident.to_string());
-
         if self.error {
+            let error_var = to_var(ident);
             return quote!{
-                #states_enum::#error_ident(#error_ident(#error_var)) => {
+                #states_enum::#ident(#error_var) => {
+                    let #ident(#error_var) = #state_value;
                     return Err(#error_var);
                 }
             };
         }
 
+        let boxed_states = &*self.extra.boxed_states;
+
+        let state_kind = &*self.extra.state_kind;
+        let record_path = |to: &syn::Ident| {
+            let path = match self.extra.path_field {
+                Some(ref field) => {
+                    let field = &**field;
+                    quote! {
+                        self.#field.push(#state_kind::#to);
+                    }
+                }
+                None => quote! {},
+            };
+            let events = match self.extra.events_field {
+                Some(ref field) => {
+                    let field = &**field;
+                    quote! {
+                        if let Some(ref mut events) = self.#field {
+                            events.push(#state_kind::#to);
+                        }
+                    }
+                }
+                None => quote! {},
+            };
+            let cover = match self.extra.cover {
+                Some(ref cover) => {
+                    let cover = &**cover;
+                    let name = to.to_string();
+                    quote! { #cover(#name); }
+                }
+                None => quote! {},
+            };
+            quote! { #path #events #cover }
+        };
+
+        let on_transition = |to: &syn::Ident| {
+            let record_path = record_path(to);
+            match self.extra.on_transition {
+                Some(ref hook) => {
+                    let hook = &**hook;
+                    let description_ident = &*self.extra.description_ident;
+                    quote! {
+                        #record_path
+                        <#description_ident #ty_generics as #poll_trait #ty_generics>::#hook(
+                            #state_kind::#ident,
+                            #state_kind::#to
+                        );
+                    }
+                }
+                None => record_path,
+            }
+        };
+
+        // Errors move the machine into the error state, or straight out of
+        // `poll` if there is no error state.
+        let on_error = match self.extra.error_ident {
+            Some(ref error_ident) => {
+                let error_ident = &**error_ident;
+                let error_value = box_if(
+                    boxed_states.contains(error_ident),
+                    quote! { #error_ident(e) },
+                );
+                let on_transition = on_transition(error_ident);
+                quote! {
+                    #on_transition
+                    Some(#states_enum::#error_ident(#error_value))
+                }
+            }
+            None => quote! {
+                return Err(e);
+            },
+        };
+
+        // Enter the state `to` with the value in `to_var`, running its
+        // `on_enter` hook if it has one. A fallible hook's error is handled
+        // just like an error from polling.
+        let description_ident = &*self.extra.description_ident;
+        let hook_on_error = on_error.clone();
+        let enter = |to: &syn::Ident, to_var: &quote::Ident| {
+            let to_value = box_if(boxed_states.contains(to), to_var);
+            let on_transition = on_transition(to);
+            let entered = quote! {
+                {
+                    #on_transition
+                    Some(#states_enum::#to(#to_value))
+                }
+            };
+            let hook = quote::Ident::new(format!("on_enter_{}", to.as_ref().to_snake_case()));
+            let hook = quote! {
+                <#description_ident #ty_generics as #poll_trait #ty_generics>::#hook(&mut #to_var)
+            };
+            match self.extra.on_enter.get(to) {
+                None => entered,
+                Some(&false) => quote! {
+                    {
+                        #hook;
+                        #entered
+                    }
+                },
+                Some(&true) => quote! {
+                    match #hook {
+                        Ok(()) => #entered,
+                        Err(e) => {
+                            #hook_on_error
+                        }
+                    }
+                },
+            }
+        };
+
+        if self.yields {
+            let next = &self.transitions[0];
+            let mut_next = if self.extra.on_enter.contains_key(next) {
+                quote! { mut next }
+            } else {
+                quote! { next }
+            };
+            let enter_next = enter(next, &quote::Ident::new("next"));
+            return quote! {
+                #states_enum::#ident(#var) => {
+                    let #ident(item, #mut_next) = #state_value;
+                    self.0 = #enter_next;
+                    return Ok(#futures_crate::Async::Ready(Some(item)));
+                }
+            };
+        }
+
+        // Convert a state's own error type into the state machine's.
+        let on_error = match self.error_type {
+            Some(_) => {
+                let machine_error = &*self.extra.error_type;
+                quote! {
+                    let e: #machine_error = ::std::convert::Into::into(e);
+                    #on_error
+                }
+            }
+            None => on_error,
+        };
+
         let after = &self.extra.after;
         let poll_method = &self.extra.poll_method;
-        let description_ident = &*self.extra.description_ident;
 
         let ready = self.transitions.iter().map(|t| {
-            let t_var = to_var(t.to_string());
+            let t_var = to_var(t);
+            let mut_t_var = if self.extra.on_enter.contains_key(t) {
+                quote! { mut #t_var }
+            } else {
+                quote! { #t_var }
+            };
+            let enter_t = enter(t, &t_var);
             quote! {
-                Ok(#futures_crate::Async::Ready(#after::#t(#t_var))) => {
-                    Some(#states_enum::#t(#t_var))
-                }
+                Ok(#futures_crate::Async::Ready(#after::#t(#mut_t_var))) => #enter_t
             }
         });
 
+        let not_ready_value = if self.boxed {
+            quote! { #var.map(|#var| #states_enum::#ident(::std::boxed::Box::new(#var))) }
+        } else {
+            quote! { #var.map(#states_enum::#ident) }
+        };
+
+        // Staying is not a transition, so it skips the transition hooks, and
+        // just puts the state back to be polled again.
+        let stay = if self.stay {
+            quote! {
+                Ok(#futures_crate::Async::Ready(#after::Stay)) => {
+                    self.0 = #not_ready_value;
+                    continue;
+                }
+            }
+        } else {
+            quote! {}
+        };
+
+        let poll = quote! {
+            <#description_ident #ty_generics as #poll_trait #ty_generics>::#poll_method
+        };
+        let polled = match self.repoll {
+            // Poll again while the method fails without taking the state, up to
+            // `max` more times, before handling the last error as usual.
+            Some(ref repoll) => {
+                let max = repoll.max;
+                quote! {
+                    let mut #var = #state_value;
+                    let mut repolls = 0u32;
+                    let (#var, result) = loop {
+                        match #rent_to_own::with(#var, #poll) {
+                            (Some(state), Err(_)) if repolls < #max => {
+                                repolls += 1;
+                                #var = state;
+                            }
+                            polled => break polled,
+                        }
+                    };
+                }
+            }
+            None => quote! {
+                let (#var, result) = #rent_to_own::with(#state_value, #poll);
+            },
+        };
+
         quote! {
             #states_enum::#ident(#var) => {
-                let (#var, result) =
-                    #smf_crate::RentToOwn::with(
-                        #var,
-                        <#description_ident #ty_generics as #poll_trait #ty_generics>::#poll_method
-                    );
+                #polled
                 match result {
                     Err(e) => {
-                        Some(#states_enum::#error_ident(#error_ident(e)))
+                        #on_error
                     }
                     Ok(#futures_crate::Async::NotReady) => {
-                        self.0 = #var.map(#states_enum::#ident);
+                        self.0 = #not_ready_value;
                         return Ok(#futures_crate::Async::NotReady);
                     }
+                    #stay
                     #( #ready )*
                 }
             }
@@ -380,7 +1777,7 @@ impl State<phases::ReadyForCodegen> {
              `Ok(Async::NotReady)`. If an error is encountered, return `Err({})`. \
              The `RentToOwn` wrapper allows you to choose whether to take \
              ownership of the current state or not.",
-            self.ident.to_string(),
+            self.ident,
             self.extra.after,
             {
                 let mut t = quote!{};
@@ -399,14 +1796,17 @@ impl State<phases::ReadyForCodegen> {
         let after = &self.extra.after;
         let ty_generics = self.extra.generics.split_for_impl().1;
         let (_, after_ty_generics, _) = self.extra.after_state_generics.split_for_impl();
-        let error_type = &*self.extra.error_type;
+        let error_type = match self.error_type {
+            Some(ref error_type) => error_type,
+            None => &*self.extra.error_type,
+        };
         let futures_crate = &*self.extra.futures_crate;
-        let smf_crate = &*self.extra.smf_crate;
+        let rent_to_own = &*self.extra.rent_to_own;
 
         quote! {
             #poll_method_doc
             fn #poll_method<'smf_poll>(
-                &'smf_poll mut #smf_crate::RentToOwn<'smf_poll, #me #ty_generics>
+                &'smf_poll mut #rent_to_own<'smf_poll, #me #ty_generics>
             ) -> #futures_crate::Poll<#after #after_ty_generics, #error_type>;
         }
     }
@@ -436,7 +1836,7 @@ impl ToTokens for State<phases::ReadyForCodegen> {
             .iter()
             .map(|f| {
                 let mut f = f.clone();
-                f.vis = syn::Visibility::Public;
+                f.vis = vis.clone();
                 f
             })
             .collect();
@@ -468,10 +1868,21 @@ impl ToTokens for State<phases::ReadyForCodegen> {
 
         let after_ident = &self.extra.after;
 
-        let after_variants: Vec<_> = self.extra
-            .transition_state_generics
+        // Only this state's lint levels, and not its docs, apply to the items
+        // generated for it.
+        let allows: Vec<_> = attrs.iter().filter(|a| a.name() == "allow").collect();
+        let allows = &allows;
+
+        // Follow the declared `transitions` order, so that the generated code,
+        // and any derived `Ord`, are the same from build to build.
+        let transitions: Vec<_> = self.transitions
+            .iter()
+            .map(|s| (s, &self.extra.transition_state_generics[s]))
+            .collect();
+
+        let mut after_variants: Vec<_> = transitions
             .iter()
-            .map(|(s, g)| {
+            .map(|&(s, g)| {
                 let doc = doc_string(format!(
                     "A transition from the `{}` state to the `{}` state.",
                     ident_name, s
@@ -483,15 +1894,25 @@ impl ToTokens for State<phases::ReadyForCodegen> {
                 }
             })
             .collect();
+        if self.stay {
+            let doc = doc_string(format!(
+                "Stay in the `{}` state, and poll it again straight away.",
+                ident_name
+            ));
+            after_variants.push(quote! {
+                #doc
+                Stay
+            });
+        }
 
-        let after_froms: Vec<_> = self.extra
-            .transition_state_generics
+        let after_froms: Vec<_> = transitions
             .iter()
-            .map(|(s, g)| {
-                let s_var = to_var(s.to_string());
+            .map(|&(s, g)| {
+                let s_var = to_var(s);
                 let trans_ty_generics = g.split_for_impl().1;
 
                 quote! {
+                    #( #allows )*
                     impl #after_impl_generics From<#s #trans_ty_generics>
                         for #after_ident #after_ty_generics #after_where_clause {
                         fn from(#s_var: #s #trans_ty_generics) -> Self {
@@ -509,6 +1930,9 @@ impl ToTokens for State<phases::ReadyForCodegen> {
 
         tokens.append(quote! {
             #after_doc
+            #( #allows )*
+            #derive
+            #[must_use]
             #vis enum #after_ident #after_impl_generics #after_where_clause {
                 #( #after_variants ),*
             }
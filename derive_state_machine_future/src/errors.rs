@@ -0,0 +1,124 @@
+//! Errors in the shape of our attributes, reported at the offending tokens.
+//!
+//! `syn` cannot parse every malformed attribute (`transitions = Ready` is not a
+//! meta item at all), and the errors it and `darling` do give us have no span.
+//! So before parsing, we look over the raw tokens for the mistakes that people
+//! actually make, and turn them into a `compile_error!` that points at them.
+//!
+//! Validation errors about a single state are reported the same way, at the
+//! state's variant; see `phases::StateError`.
+
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+
+/// What a state's `transitions` should look like, for error messages.
+pub const TRANSITIONS_SHAPE: &str = "expected `transitions(State1, State2)`";
+
+/// Check the state machine description's attributes, returning a
+/// `compile_error!` for the first malformed one.
+pub fn check(input: &TokenStream) -> Option<TokenStream> {
+    body(input)?
+        .into_iter()
+        .filter_map(|tt| match tt {
+            TokenTree::Group(ref g) if g.delimiter() == Delimiter::Bracket => {
+                attribute_args(g.stream())
+            }
+            _ => None,
+        })
+        .flat_map(split_commas)
+        .find_map(|arg| check_transitions(&arg))
+}
+
+/// A `compile_error!` with the given message, pointing at the variant for
+/// `state`.
+pub fn at_state(input: &TokenStream, state: &str, message: &str) -> TokenStream {
+    compile_error(message, variant_span(input, state))
+}
+
+/// Find the span of the variant for `state` in the state machine
+/// description, or fall back to the derive's call site.
+///
+/// Only the variants themselves are idents at the top level of the `enum`'s
+/// body; every other mention of a state, such as a target in another state's
+/// `transitions(...)`, is nested inside an attribute or a field list.
+pub fn variant_span(input: &TokenStream, state: &str) -> Span {
+    body(input)
+        .into_iter()
+        .flatten()
+        .find_map(|tt| match tt {
+            TokenTree::Ident(ref ident) if ident.to_string() == state => Some(ident.span()),
+            _ => None,
+        })
+        .unwrap_or_else(Span::call_site)
+}
+
+/// The body of the state machine description `enum`.
+fn body(input: &TokenStream) -> Option<TokenStream> {
+    input.clone().into_iter().find_map(|tt| match tt {
+        TokenTree::Group(ref g) if g.delimiter() == Delimiter::Brace => Some(g.stream()),
+        _ => None,
+    })
+}
+
+/// The arguments of a `state_machine_future(...)` attribute.
+fn attribute_args(attr: TokenStream) -> Option<TokenStream> {
+    let mut tokens = attr.into_iter();
+    match (tokens.next(), tokens.next(), tokens.next()) {
+        (Some(TokenTree::Ident(ref name)), Some(TokenTree::Group(ref args)), None)
+            if name.to_string() == "state_machine_future"
+                && args.delimiter() == Delimiter::Parenthesis =>
+        {
+            Some(args.stream())
+        }
+        _ => None,
+    }
+}
+
+fn split_commas(args: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut split = vec![vec![]];
+    for tt in args {
+        match tt {
+            TokenTree::Punct(ref p) if p.as_char() == ',' => split.push(vec![]),
+            tt => split.last_mut().unwrap().push(tt),
+        }
+    }
+    split
+}
+
+fn check_transitions(arg: &[TokenTree]) -> Option<TokenStream> {
+    let span = match arg.first() {
+        Some(TokenTree::Ident(ref ident)) if ident.to_string() == "transitions" => ident.span(),
+        _ => return None,
+    };
+    match arg.get(1) {
+        Some(TokenTree::Group(ref g))
+            if g.delimiter() == Delimiter::Parenthesis && arg.len() == 2 =>
+        {
+            None
+        }
+        _ => {
+            let found: TokenStream = arg.iter().cloned().collect();
+            Some(compile_error(
+                &format!("{}, found `{}`", TRANSITIONS_SHAPE, found),
+                span,
+            ))
+        }
+    }
+}
+
+fn compile_error(message: &str, span: Span) -> TokenStream {
+    let mut message = Literal::string(message);
+    message.set_span(span);
+    let mut bang = Punct::new('!', Spacing::Alone);
+    bang.set_span(span);
+    let mut args = Group::new(Delimiter::Parenthesis, TokenTree::Literal(message).into());
+    args.set_span(span);
+    let mut semi = Punct::new(';', Spacing::Alone);
+    semi.set_span(span);
+    vec![
+        TokenTree::Ident(Ident::new("compile_error", span)),
+        TokenTree::Punct(bang),
+        TokenTree::Group(args),
+        TokenTree::Punct(semi),
+    ].into_iter()
+        .collect()
+}
@@ -13,17 +13,25 @@ extern crate syn;
 
 mod ast;
 mod codegen;
+mod errors;
 mod phases;
+mod warnings;
 
 use ast::StateMachine;
 use darling::FromDeriveInput;
 use phases::Pass;
 use proc_macro::TokenStream;
 use quote::ToTokens;
+use std::panic;
 
 #[proc_macro_derive(StateMachineFuture, attributes(state_machine_future))]
 pub fn derive_state_machine_future(tokens: TokenStream) -> TokenStream {
     let source = tokens.to_string();
+    let source_tokens = tokens;
+
+    if let Some(error) = errors::check(&source_tokens) {
+        return error;
+    }
 
     let derive_input =
         syn::parse_derive_input(&source).expect("should parse source into derive input");
@@ -33,17 +41,34 @@ pub fn derive_state_machine_future(tokens: TokenStream) -> TokenStream {
         Err(e) => panic!("error in derive(StateMachineFuture): {}", e),
     };
 
-    let machine = phases::StartReadyError::pass(machine);
-    let machine = phases::ValidTransitionEdges::pass(machine);
-    let machine = phases::ValidPaths::pass(machine);
-    let machine = phases::StateGenerics::pass(machine);
-    let machine = phases::AfterStateGenerics::pass(machine);
-    let machine = phases::ReadyForCodegen::pass(machine);
+    // Errors about a single state unwind with a `StateError`, which we report
+    // at that state's variant. Anything else is a plain panic.
+    let machine = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        let machine = phases::StartReadyError::pass(machine);
+        let machine = phases::ValidTransitionEdges::pass(machine);
+        let machine = phases::ValidPaths::pass(machine);
+        let machine = phases::StateGenerics::pass(machine);
+        let machine = phases::AfterStateGenerics::pass(machine);
+        phases::ReadyForCodegen::pass(machine)
+    }));
+    let machine = match machine {
+        Ok(machine) => machine,
+        Err(payload) => match payload.downcast::<phases::StateError>() {
+            Ok(error) => {
+                return errors::at_state(&source_tokens, error.state.as_ref(), &error.message)
+            }
+            Err(payload) => panic::resume_unwind(payload),
+        },
+    };
+
+    let warnings = machine.warnings();
 
     let mut tokens = quote!();
     machine.to_tokens(&mut tokens);
 
-    tokens
+    let mut output: TokenStream = tokens
         .parse()
-        .expect("should parse expanded output source into tokens")
+        .expect("should parse expanded output source into tokens");
+    warnings::emit(&machine, &warnings, &source_tokens, &mut output);
+    output
 }
@@ -32,6 +32,44 @@ macro_rules! dummy_default {
     }
 }
 
+/// A validation error about a single state, which `derive_state_machine_future`
+/// reports at that state's variant rather than at the whole derive.
+///
+/// Passes raise these with `state_error!` and `check_state!`, which unwind
+/// with the error as the payload, so that validation can stay written as a
+/// series of assertions.
+#[derive(Debug)]
+pub struct StateError {
+    pub state: syn::Ident,
+    pub message: String,
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+// Fail validation with a `StateError` about the given state. This uses
+// `resume_unwind` so that no panic message is printed for it.
+macro_rules! state_error {
+    ( $state:expr, $( $arg:tt )* ) => {
+        ::std::panic::resume_unwind(Box::new(::phases::StateError {
+            state: ::std::clone::Clone::clone(&$state),
+            message: format!( $( $arg )* ),
+        }))
+    }
+}
+
+// Like `assert!`, but fails with a `StateError` about the given state.
+macro_rules! check_state {
+    ( $state:expr, $cond:expr, $( $arg:tt )* ) => {
+        if !$cond {
+            state_error!($state, $( $arg )*);
+        }
+    }
+}
+
 /// A phase represents a state in the pipeline, and the extra data we've
 /// accumulated up to this point.
 pub trait Phase: fmt::Debug + darling::FromMetaItem {
@@ -66,7 +104,7 @@ pub trait Pass: Phase {
     type FromPhase: Phase;
 
     /// The function to translate between these phases.
-    fn pass(StateMachine<Self::FromPhase>) -> StateMachine<Self>;
+    fn pass(_: StateMachine<Self::FromPhase>) -> StateMachine<Self>;
 }
 
 /// The state machine AST has been parsed from the custom derive input.
@@ -78,13 +116,13 @@ impl Phase for Parsed {
     type StateExtra = ();
 }
 
-/// We've found the indices into `states` for the unique start, ready, and error
-/// states.
+/// We've found the indices into `states` for the unique start and ready states,
+/// and the error state if there is one.
 #[derive(FromMetaItem, Debug, Default)]
 pub struct StartReadyError {
     pub start: usize,
     pub ready: usize,
-    pub error: usize,
+    pub error: Option<usize>,
 }
 
 impl Phase for StartReadyError {
@@ -97,59 +135,281 @@ impl Pass for StartReadyError {
 
     fn pass(machine: StateMachine<Parsed>) -> StateMachine<StartReadyError> {
         machine.and_then(|machine, (), states| {
-            let mut start = None;
-            let mut ready = None;
-            let mut error = None;
+            // Each of these is the index and ident of the state marked with the
+            // corresponding attribute, if we've seen it yet.
+            let mut start: Option<(usize, syn::Ident)> = None;
+            let mut ready: Option<(usize, syn::Ident)> = None;
+            let mut error: Option<(usize, syn::Ident)> = None;
+
+            let stream = machine.stream;
+            let mut yields = false;
+
+            let state_kind = format!("{}State", machine.ident);
+            let has_state_enum = machine.has_state_enum();
+
+            // Report every `error` state at once, rather than the first two.
+            let errors: Vec<_> = states.iter().filter(|s| s.error).map(|s| &s.ident).collect();
+            if errors.len() > 1 {
+                let second = errors[1];
+                let names = |states: &[&syn::Ident]| {
+                    join_names(states.iter().map(|e| format!("`{}`", e)).collect())
+                };
+                let (all, extra) = if errors.len() == 2 {
+                    ("both", "state")
+                } else {
+                    ("all", "states")
+                };
+                state_error!(
+                    second,
+                    "Only one state may be marked `error`, but {} are {} marked `error`; \
+                     remove `error` from the extra {} {}",
+                    names(&errors),
+                    all,
+                    extra,
+                    names(&errors[1..])
+                );
+            }
 
-            let states = states
+            let mut states: Vec<_> = states
                 .into_iter()
                 .enumerate()
                 .map(|(idx, state)| {
+                    let name = &state.ident;
+
+                    check_state!(
+                        name,
+                        !has_state_enum || name.as_ref() != state_kind,
+                        "The `{}` state has the same name as the generated `{}` enum of \
+                         states; rename it",
+                        name,
+                        state_kind
+                    );
+
                     if state.start {
-                        assert!(start.is_none(), "There must only be a single `start` state");
-                        start = Some(idx);
+                        if let Some((_, ref other)) = start {
+                            state_error!(
+                                name,
+                                "There must only be a single `start` state, but both `{}` and \
+                                 `{}` are marked `start`",
+                                other, name
+                            );
+                        }
+                        start = Some((idx, name.clone()));
                     }
 
+                    check_state!(
+                        name,
+                        !(state.ready && state.error),
+                        "The `{}` state is marked both `ready` and `error`, but success and \
+                         failure must be different states; add a separate `error` state",
+                        name
+                    );
+
                     if state.ready {
-                        assert!(ready.is_none(), "There must only be a single `ready` state");
-                        assert!(
+                        if let Some((_, ref other)) = ready {
+                            state_error!(
+                                name,
+                                "There must only be a single `ready` state, but both `{}` and \
+                                 `{}` are marked `ready`",
+                                other, name
+                            );
+                        }
+                        check_state!(
+                            name,
                             state.data.style.is_tuple(),
-                            "The `ready` state must be a tuple variant, for example: `Ready(Item)`"
+                            "The `ready` state must be a tuple variant, for example: \
+                             `Ready(Item)`, but `{}` is not",
+                            name
+                        );
+                        check_state!(
+                            name,
+                            state.data.fields.len() == 1,
+                            "The `ready` state must only have one field, for example: \
+                             `Ready(Item)`, but `{}` does not",
+                            name
                         );
-                        assert_eq!(
-                            state.data.fields.len(),
-                            1,
-                            "The `ready` state must only have one field, for example: `Ready(Item)`"
+                        check_state!(
+                            name,
+                            !state.start || state.transitions.is_empty(),
+                            "The `{}` state is marked both `start` and `ready`, so the state \
+                             machine resolves immediately and cannot have transitions; either \
+                             remove its `transitions` or add a separate `ready` state",
+                            name
                         );
-                        assert!(
+                        check_state!(
+                            name,
                             state.transitions.is_empty(),
-                            "The `ready` state must not transition to any other states"
+                            "The `ready` state must not transition to any other states, but \
+                             `{}` does",
+                            name
                         );
-                        ready = Some(idx);
+                        ready = Some((idx, name.clone()));
                     }
 
                     if state.error {
-                        assert!(error.is_none(), "There must only be a single `error` state");
-                        assert!(
+                        check_state!(
+                            name,
                             state.data.style.is_tuple(),
-                            "The `error` state must be a tuple variant, for example: `Error(Item)`"
+                            "The `error` state must be a tuple variant, for example: \
+                             `Error(Item)`, but `{}` is not",
+                            name
                         );
-                        assert_eq!(
-                            state.data.fields.len(),
-                            1,
-                            "The `error` state must only have one field, for example: `Error(Item)`"
+                        check_state!(
+                            name,
+                            state.data.fields.len() == 1,
+                            "The `error` state must only have one field, for example: \
+                             `Error(Item)`, but `{}` does not",
+                            name
                         );
-                        assert!(
+                        check_state!(
+                            name,
                             state.transitions.is_empty(),
-                            "The `error` state must not transition to any other states"
+                            "The `error` state must not transition to any other states, but \
+                             `{}` does",
+                            name
                         );
-                        error = Some(idx);
+                        error = Some((idx, name.clone()));
                     }
 
-                    if !state.ready && !state.error {
-                        assert!(
-                            !state.transitions.is_empty(),
-                            "Non-{ready,error} states must have transitions"
+                    if state.yields {
+                        check_state!(
+                            name,
+                            stream,
+                            "The `{}` state is marked `yields`, but the state machine is not \
+                             marked `stream`",
+                            name
+                        );
+                        check_state!(
+                            name,
+                            !state.ready && !state.error,
+                            "The `{}` state cannot both be a `yields` state and a `ready` or \
+                             `error` state",
+                            name
+                        );
+                        check_state!(
+                            name,
+                            state.data.style.is_tuple() && state.data.fields.len() == 2,
+                            "A `yields` state must be a tuple variant of the yielded item and \
+                             the next state, for example: `Yield(Item, Next)`, but `{}` is not",
+                            name
+                        );
+                        check_state!(
+                            name,
+                            state.transitions.len() == 1,
+                            "A `yields` state must transition to exactly one state, the next \
+                             state it holds, but `{}` does not",
+                            name
+                        );
+                        yields = true;
+                    }
+
+                    check_state!(
+                        name,
+                        state.error_type.is_none() || !(state.ready || state.error || state.yields),
+                        "The `{}` state has no polling trait method, so it cannot have an \
+                         `error_type`",
+                        name
+                    );
+
+                    check_state!(
+                        name,
+                        !state.finish || !(state.ready || state.error || state.yields),
+                        "The `{}` state is marked `finish`, but only states with a polling \
+                         trait method can transition to the `ready` state",
+                        name
+                    );
+
+                    check_state!(
+                        name,
+                        state.on_enter.is_none() || !state.error,
+                        "The `{}` state is the error state, so it cannot have an `on_enter` \
+                         hook",
+                        name
+                    );
+
+                    check_state!(
+                        name,
+                        !state.on_exit || !(state.ready || state.error || state.yields),
+                        "The `{}` state has an `on_exit` hook, but the state machine never \
+                         waits in it, so it could never be dropped there",
+                        name
+                    );
+
+                    check_state!(
+                        name,
+                        state.poll_fn.is_none() || !(state.ready || state.error || state.yields),
+                        "The `{}` state has a `poll_fn`, but it does not have a polling trait \
+                         method to rename",
+                        name
+                    );
+
+                    check_state!(
+                        name,
+                        state.repoll.is_none() || !(state.ready || state.error || state.yields),
+                        "The `{}` state has a `repoll` policy, but it has no polling trait \
+                         method to poll again",
+                        name
+                    );
+
+                    check_state!(
+                        name,
+                        !state.stay || !(state.ready || state.error || state.yields),
+                        "The `{}` state is marked `stay`, but it has no polling trait method \
+                         to stay from",
+                        name
+                    );
+
+                    check_state!(
+                        name,
+                        !state.stay || !state.transitions.contains(&syn::Ident::new("Stay")),
+                        "The `{}` state is marked `stay`, so its `After{}` enum already has a \
+                         `Stay` variant and cannot also transition to a state named `Stay`",
+                        name,
+                        name
+                    );
+
+                    check_state!(
+                        name,
+                        !state.entry || !(state.start || state.ready || state.error),
+                        "The `{}` state is marked `entry`, but only intermediate states can \
+                         be entry states; the start state is always one already",
+                        name
+                    );
+
+                    if !state.ready && !state.error && !state.finish
+                        && state.transitions.is_empty()
+                    {
+                        if state.start {
+                            state_error!(
+                                name,
+                                "The start state `{}` has no transitions and is not the \
+                                 `ready` state, so the state machine could never leave it; \
+                                 list the states it can move to in `transitions(...)`, or \
+                                 mark it `ready` for a state machine that resolves \
+                                 immediately",
+                                name
+                            );
+                        }
+                        if state.transitions.declared {
+                            state_error!(
+                                name,
+                                "The `{}` state has an empty `transitions()` list; either add \
+                                 a state for it to transition to, or mark it `ready` or `error`",
+                                name
+                            );
+                        }
+                        let dead_fields = if state.data.fields.is_empty() {
+                            ""
+                        } else {
+                            ", otherwise the data it stores could never be consumed"
+                        };
+                        state_error!(
+                            name,
+                            "The `{}` state must have transitions, because it is neither the \
+                             `ready` state nor the `error` state; add \
+                             `#[state_machine_future(transitions(...))]`{}",
+                            name,
+                            dead_fields
                         );
                     }
 
@@ -157,9 +417,30 @@ impl Pass for StartReadyError {
                 })
                 .collect();
 
-            let start = start.expect("Must specify one `start` state");
-            let ready = ready.expect("Must specify one `ready` state");
-            let error = error.expect("Must specify one `error` state");
+            assert!(
+                !stream || yields,
+                "A `stream` state machine must have at least one `yields` state"
+            );
+
+            assert!(
+                !stream || !machine.sync,
+                "A `stream` state machine cannot be `sync`; poll the `Stream` instead"
+            );
+
+            assert!(
+                !stream || !machine.transition_events,
+                "A `stream` state machine cannot have `transition_events`; use `record_path` \
+                 to see the states it enters"
+            );
+
+            let start = start.expect("Must specify one `start` state").0;
+            let (ready, ready_ident) = ready.expect("Must specify one `ready` state");
+            let error = error.map(|(idx, _)| idx);
+
+            // `finish` is shorthand for listing the ready state in `transitions`.
+            for state in states.iter_mut().filter(|s| s.finish) {
+                state.transitions.add(ready_ident.clone());
+            }
 
             machine.join(
                 StartReadyError {
@@ -173,6 +454,16 @@ impl Pass for StartReadyError {
     }
 }
 
+/// Join names for an error message: "`A` and `B`", or "`A`, `B`, and `C`".
+fn join_names(mut names: Vec<String>) -> String {
+    let last = names.pop().unwrap();
+    match names.len() {
+        0 => last,
+        1 => format!("{} and {}", names[0], last),
+        _ => format!("{}, and {}", names.join(", "), last),
+    }
+}
+
 /// A phase after which we know that all transitions are to valid states. That
 /// is, we will never get any "cannot find type `UnknownState` in this scope"
 /// compilation errors from any code we emit.
@@ -191,19 +482,33 @@ impl Pass for ValidTransitionEdges {
         machine.and_then(|machine, extra, states| {
             let state_idents: HashSet<syn::Ident> =
                 HashSet::from_iter(states.iter().map(|s| s.ident.clone()));
+            let start_ident = states[extra.start].ident.clone();
+            let no_start_loops = machine.no_start_loops;
 
             let states = states
                 .into_iter()
                 .map(|state| {
                     state.and_then(|s, ()| {
                         s.transitions.iter().for_each(|t| {
-                            assert!(
+                            check_state!(
+                                s.ident,
                                 state_idents.contains(t),
-                                "Transition to unknown state `{}` from state `{}`",
-                                t.to_string(),
-                                s.ident.to_string()
+                                "Transition to unknown state `{}` from state `{}`; if `{}` \
+                                 is behind `#[cfg(...)]`, then its incoming `transitions` must \
+                                 be behind a matching `#[cfg_attr(...)]`",
+                                t,
+                                s.ident,
+                                t
                             );
                         });
+                        check_state!(
+                            s.ident,
+                            !no_start_loops || !s.transitions.contains(&start_ident),
+                            "The `{}` state transitions back to the start state `{}`, but the \
+                             state machine has `no_start_loops`",
+                            s.ident,
+                            start_ident
+                        );
                         s.join(())
                     })
                 })
@@ -228,6 +533,7 @@ impl Pass for ValidPaths {
     type FromPhase = ValidTransitionEdges;
 
     fn pass(machine: StateMachine<ValidTransitionEdges>) -> StateMachine<ValidPaths> {
+        let parsed = machine.to_string();
         machine.and_then(|machine, extra, states| {
             let mut nodes: HashMap<String, petgraph::graph::NodeIndex<_>> = HashMap::new();
             let mut graph: petgraph::Graph<String, ()> = petgraph::Graph::new();
@@ -254,14 +560,18 @@ impl Pass for ValidPaths {
 
             let start_name = states[extra.start].ident.to_string();
             let ready_name = states[extra.ready].ident.to_string();
-            let error_name = states[extra.error].ident.to_string();
-            let start = nodes[&start_name];
+            let error_name = extra.error.map(|e| states[e].ident.to_string());
+            let entries: Vec<_> = states
+                .iter()
+                .filter(|s| s.start || s.entry)
+                .map(|s| nodes[&s.ident.to_string()])
+                .collect();
             let ready = nodes[&ready_name];
-            let error = nodes[&error_name];
+            let error = error_name.as_ref().map(|e| nodes[e]);
 
             // Check that every non-final state is
             //
-            // 1. Reachable from the start state, or is the start state, and
+            // 1. Reachable from the start state or an entry state, or is one of them, and
             // 2. Has a path leading to a final state (ready or error).
             //
             // TODO: This would be a lot more efficient if we didn't throw away
@@ -269,28 +579,104 @@ impl Pass for ValidPaths {
             // means not using `has_path_connecting` and rolling our own thing,
             // which is more work than I want to do this moment.
             let mut dfs_space = petgraph::algo::DfsSpace::new(&graph);
+
+            // Report unreachable states that transition between each other as
+            // one island, rather than one at a time.
+            let unreachable: HashSet<_> = states
+                .iter()
+                .filter(|s| !s.ready && !s.error && !s.start && !s.entry)
+                .map(|s| nodes[&s.ident.to_string()])
+                .filter(|&node| {
+                    !entries.iter().any(|&entry| {
+                        has_path_connecting(&graph, entry, node, Some(&mut dfs_space))
+                    })
+                })
+                .collect();
+            let mut visited = HashSet::new();
+            for s in &states {
+                let node = nodes[&s.ident.to_string()];
+                if !unreachable.contains(&node) || !visited.insert(node) {
+                    continue;
+                }
+
+                let mut island = vec![node];
+                let mut i = 0;
+                while i < island.len() {
+                    for neighbor in graph.neighbors_undirected(island[i]) {
+                        if unreachable.contains(&neighbor) && visited.insert(neighbor) {
+                            island.push(neighbor);
+                        }
+                    }
+                    i += 1;
+                }
+
+                if island.len() > 1 {
+                    let mut names: Vec<_> =
+                        island.iter().map(|&n| format!("`{}`", graph[n])).collect();
+                    names.sort();
+                    let names = join_names(names);
+                    state_error!(
+                        s.ident,
+                        "The {} states form an island: they transition between each other, \
+                         but none of them are reachable from the start state (`{}`) or an \
+                         `entry` state, in:\n{}",
+                        names,
+                        start_name,
+                        parsed
+                    );
+                }
+            }
+
             states
                 .iter()
                 .filter(|s| !s.ready && !s.error)
                 .for_each(|s| {
                     let s_name = s.ident.to_string();
                     let s_node = nodes[&s_name];
-                    assert!(
-                        has_path_connecting(&graph, s_node, ready, Some(&mut dfs_space))
-                            || has_path_connecting(&graph, s_node, error, Some(&mut dfs_space)),
-                        "The `{}` state must have a transition path to either the ready \
-                         state (`{}`) or the error state (`{}`) but it does not",
-                        s_name,
-                        ready_name,
-                        error_name
-                    );
+                    let reaches_final = has_path_connecting(
+                        &graph,
+                        s_node,
+                        ready,
+                        Some(&mut dfs_space),
+                    ) || match error {
+                        Some(error) => {
+                            has_path_connecting(&graph, s_node, error, Some(&mut dfs_space))
+                        }
+                        None => false,
+                    };
+                    match error_name {
+                        Some(ref error_name) => check_state!(
+                            s.ident,
+                            reaches_final,
+                            "The `{}` state must have a transition path to either the ready \
+                             state (`{}`) or the error state (`{}`) but it does not, in:\n{}",
+                            s_name,
+                            ready_name,
+                            error_name,
+                            parsed
+                        ),
+                        None => check_state!(
+                            s.ident,
+                            reaches_final,
+                            "The `{}` state must have a transition path to the ready state \
+                             (`{}`) but it does not, in:\n{}",
+                            s_name,
+                            ready_name,
+                            parsed
+                        ),
+                    }
 
-                    assert!(
-                        s.start || has_path_connecting(&graph, start, s_node, Some(&mut dfs_space)),
-                        "The `{}` state must be reachable from the start state (`{}`) but \
-                         it is not",
+                    let reachable = entries.iter().any(|&entry| {
+                        has_path_connecting(&graph, entry, s_node, Some(&mut dfs_space))
+                    });
+                    check_state!(
+                        s.ident,
+                        s.start || s.entry || reachable,
+                        "The `{}` state must be reachable from the start state (`{}`) or an \
+                         `entry` state but it is not, in:\n{}",
                         s_name,
-                        start_name
+                        start_name,
+                        parsed
                     );
                 });
 
@@ -364,7 +750,7 @@ impl Pass for StateGenerics {
             F: Fn(&HashSet<syn::Ident>, &S) -> bool,
         {
             let mut iter = params.iter();
-            while let Some(&(_, ref bound, ref idents)) = iter.next() {
+            while let Some((_, bound, idents)) = iter.next() {
                 if contains_bound(state_idents, bound) {
                     let old_len = state_idents.len();
                     state_idents.extend(idents.iter().cloned());
@@ -392,13 +778,13 @@ impl Pass for StateGenerics {
                 let where_preds = prepare_generic_params(&mgenerics.where_clause.predicates, |w| {
                     let mut bound_idents = HashSet::new();
                     match w {
-                        &syn::WherePredicate::BoundPredicate(ref bound) => {
+                        syn::WherePredicate::BoundPredicate(bound) => {
                             bound.bounded_ty.collect_idents(&mut bound_idents)
                         }
-                        &syn::WherePredicate::EqPredicate(ref eq) => {
+                        syn::WherePredicate::EqPredicate(eq) => {
                             eq.lhs_ty.collect_idents(&mut bound_idents)
                         }
-                        &syn::WherePredicate::RegionPredicate(ref region) => {
+                        syn::WherePredicate::RegionPredicate(region) => {
                             bound_idents.insert(region.lifetime.ident.clone());
                         }
                     };
@@ -442,7 +828,7 @@ impl Pass for StateGenerics {
                             // params
                             let where_preds = where_preds
                                 .iter()
-                                .filter(|&&(_, ref bounds, _)| {
+                                .filter(|&(_, bounds, _)| {
                                     bounds.iter().any(|b| state_idents.contains(b))
                                 })
                                 .map(|v| v.0)
@@ -451,25 +837,28 @@ impl Pass for StateGenerics {
 
                             let ty_params = ty_params
                                 .iter()
-                                .filter(|&&(_, ref bound, _)| state_idents.contains(bound))
+                                .filter(|&&(_, bound, _)| state_idents.contains(bound))
                                 .map(|v| v.0)
                                 .cloned()
                                 .collect();
 
                             let lifetimes = lifetimes
                                 .iter()
-                                .filter(|&&(_, ref bound, _)| state_idents.contains(bound))
+                                .filter(|&&(_, bound, _)| state_idents.contains(bound))
                                 .map(|v| v.0)
                                 .cloned()
                                 .collect();
 
-                            let generics = Rc::new(syn::Generics {
-                                lifetimes,
-                                ty_params,
-                                where_clause: syn::WhereClause {
-                                    predicates: where_preds,
+                            let generics = Rc::new(unused_params_removed(
+                                syn::Generics {
+                                    lifetimes,
+                                    ty_params,
+                                    where_clause: syn::WhereClause {
+                                        predicates: where_preds,
+                                    },
                                 },
-                            });
+                                &state.data.fields,
+                            ));
 
                             state.join(StateGenericsExtra { generics })
                         })
@@ -524,34 +913,36 @@ impl Pass for AfterStateGenerics {
                     .into_iter()
                     .map(|state| {
                         state.and_then(|state, extra| {
-                            // Filter all generic_params in the order they appear in the machine
-                            // generics.
+                            let targets = state
+                                .transitions
+                                .iter()
+                                .filter_map(|ident| ident_to_generics.get(ident))
+                                .collect::<Vec<_>>();
+
+                            // Keep the generic_params in the order they appear in the machine
+                            // generics. A target state may have trimmed some of a param's
+                            // bounds, so params are matched by ident, and the bounds that the
+                            // targets kept are unioned.
                             let lifetimes = mgenerics
                                 .lifetimes
                                 .iter()
-                                .filter(|l| {
-                                    state.transitions.iter().any(|ident| {
-                                        ident_to_generics
-                                            .get(ident)
-                                            .map(|v| v.lifetimes.contains(l))
-                                            .unwrap_or(false)
-                                    })
+                                .filter_map(|l| {
+                                    let kept = targets.iter().filter_map(|g| {
+                                        g.lifetimes.iter().find(|tl| tl.lifetime == l.lifetime)
+                                    });
+                                    union_bounds(kept, |tl| &mut tl.bounds)
                                 })
-                                .cloned()
                                 .collect::<Vec<_>>();
 
                             let ty_params = mgenerics
                                 .ty_params
                                 .iter()
-                                .filter(|t| {
-                                    state.transitions.iter().any(|ident| {
-                                        ident_to_generics
-                                            .get(ident)
-                                            .map(|v| v.ty_params.contains(t))
-                                            .unwrap_or(false)
-                                    })
+                                .filter_map(|t| {
+                                    let kept = targets.iter().filter_map(|g| {
+                                        g.ty_params.iter().find(|tt| tt.ident == t.ident)
+                                    });
+                                    union_bounds(kept, |tt| &mut tt.bounds)
                                 })
-                                .cloned()
                                 .collect::<Vec<_>>();
 
                             let where_preds = mgenerics
@@ -580,8 +971,8 @@ impl Pass for AfterStateGenerics {
                             let transition_state_generics = ident_to_generics
                                 .iter()
                                 .filter(|&(ident, _)| state.transitions.contains(ident))
-                                .map(|(ref ident, ref generics)| {
-                                    ((*ident).clone(), (*generics).clone())
+                                .map(|(ident, generics)| {
+                                    (ident.clone(), generics.clone())
                                 })
                                 .collect::<HashMap<_, _>>();
 
@@ -600,16 +991,163 @@ impl Pass for AfterStateGenerics {
     }
 }
 
+/// Merge the copies of one generic parameter that several states kept into a
+/// single parameter carrying the union of their bounds, or `None` if no state
+/// kept it.
+fn union_bounds<'a, P, B, F>(mut kept: impl Iterator<Item = &'a P>, bounds: F) -> Option<P>
+where
+    P: Clone + 'a,
+    B: PartialEq,
+    F: Fn(&mut P) -> &mut Vec<B>,
+{
+    let mut param = kept.next()?.clone();
+    for other in kept {
+        for bound in bounds(&mut other.clone()).drain(..) {
+            if !bounds(&mut param).contains(&bound) {
+                bounds(&mut param).push(bound);
+            }
+        }
+    }
+    Some(param)
+}
+
+/// Remove the parameters that the bounds of other parameters pulled into a
+/// state's generics, but which none of the state's fields mention. Because a
+/// state's struct would never use them, keeping them is an error. For example,
+/// given `<T, U: Into<T>>`, a `Start(U)` state gets `<U>`, not `<T, U: Into<T>>`.
+///
+/// Any bound, where predicate, or default that mentions a removed parameter is
+/// removed as well. Parameters constrained by an associated type binding, like
+/// `E` in `D: Trait<Type = E>`, count as used, just like they do for `rustc`.
+fn unused_params_removed(generics: syn::Generics, fields: &[syn::Field]) -> syn::Generics {
+    let mut mentioned = HashSet::new();
+    fields
+        .iter()
+        .for_each(|f| f.ty.collect_idents(&mut mentioned));
+
+    let bounds = generics
+        .ty_params
+        .iter()
+        .flat_map(|t| t.bounds.iter())
+        .chain(generics.where_clause.predicates.iter().flat_map(|p| match *p {
+            syn::WherePredicate::BoundPredicate(ref p) => p.bounds.iter(),
+            _ => [].iter(),
+        }));
+    for bound in bounds {
+        if let syn::TyParamBound::Trait(ref poly, _) = *bound {
+            for segment in &poly.trait_ref.segments {
+                if let syn::PathParameters::AngleBracketed(ref data) = segment.parameters {
+                    data.bindings
+                        .iter()
+                        .for_each(|b| b.ty.collect_idents(&mut mentioned));
+                }
+            }
+        }
+    }
+
+    let unused: HashSet<syn::Ident> = generics
+        .ty_params
+        .iter()
+        .map(|t| &t.ident)
+        .chain(generics.lifetimes.iter().map(|l| &l.lifetime.ident))
+        .filter(|ident| !mentioned.contains(*ident))
+        .cloned()
+        .collect();
+    if unused.is_empty() {
+        return generics;
+    }
+
+    let mentions_unused = |t: &dyn CollectIdents| {
+        let mut idents = HashSet::new();
+        t.collect_idents(&mut idents);
+        !idents.is_disjoint(&unused)
+    };
+
+    let lifetimes = generics
+        .lifetimes
+        .into_iter()
+        .filter(|l| !unused.contains(&l.lifetime.ident))
+        .map(|mut l| {
+            l.bounds.retain(|b| !unused.contains(&b.ident));
+            l
+        })
+        .collect();
+
+    let ty_params = generics
+        .ty_params
+        .into_iter()
+        .filter(|t| !unused.contains(&t.ident))
+        .map(|mut t| {
+            t.bounds.retain(|b| !mentions_unused(b));
+            let default_unused = match t.default {
+                Some(ref d) => mentions_unused(d),
+                None => false,
+            };
+            if default_unused {
+                t.default = None;
+            }
+            t
+        })
+        .collect();
+
+    let predicates = generics
+        .where_clause
+        .predicates
+        .into_iter()
+        .filter(|p| !mentions_unused(p))
+        .collect();
+
+    syn::Generics {
+        lifetimes,
+        ty_params,
+        where_clause: syn::WhereClause { predicates },
+    }
+}
+
+/// Whether `ty` stores a value of the type named `name` inline, rather than
+/// behind some indirection. This is a best-effort check that only looks inside
+/// tuples, arrays, and `Option`s.
+fn stores_inline(ty: &syn::Ty, name: &str) -> bool {
+    match *ty {
+        syn::Ty::Path(None, ref path) => {
+            let last = match path.segments.last() {
+                Some(last) => last,
+                None => return false,
+            };
+            if last.ident == name {
+                return true;
+            }
+            match last.parameters {
+                syn::PathParameters::AngleBracketed(ref data) if last.ident == "Option" => {
+                    data.types.iter().any(|t| stores_inline(t, name))
+                }
+                _ => false,
+            }
+        }
+        syn::Ty::Array(ref ty, _) | syn::Ty::Paren(ref ty) => stores_inline(ty, name),
+        syn::Ty::Tup(ref tys) => tys.iter().any(|t| stores_inline(t, name)),
+        _ => false,
+    }
+}
+
 /// The final state, where we have computed everything required for codegen.
 #[derive(Debug)]
 pub struct ReadyForCodegen {
+    pub vis: Rc<syn::Visibility>,
     pub start: usize,
     pub ready: usize,
-    pub error: usize,
+    pub error_type: Rc<syn::Ty>,
+    pub future: Rc<quote::Ident>,
     pub states_enum: Rc<quote::Ident>,
+    pub state_kind: Rc<quote::Ident>,
     pub poll_trait: Rc<quote::Ident>,
     pub futures_crate: Rc<quote::Ident>,
     pub smf_crate: Rc<quote::Ident>,
+    pub rent_to_own: Rc<quote::Tokens>,
+    pub path_field: Option<Rc<quote::Ident>>,
+    pub events_field: Option<Rc<quote::Ident>>,
+    /// The function that records that a state was entered, for `coverage`.
+    pub cover: Option<Rc<quote::Ident>>,
 }
 
 dummy_default!(ReadyForCodegen);
@@ -621,16 +1159,25 @@ pub struct CodegenStateExtra {
     pub description_ident: Rc<syn::Ident>,
     pub states_enum: Rc<quote::Ident>,
     pub error_type: Rc<syn::Ty>,
-    pub error_ident: Rc<syn::Ident>,
+    pub error_ident: Option<Rc<syn::Ident>>,
     pub after: quote::Ident,
     pub derive: Rc<darling::util::IdentList>,
     pub poll_trait: Rc<quote::Ident>,
     pub poll_method: quote::Ident,
     pub futures_crate: Rc<quote::Ident>,
-    pub smf_crate: Rc<quote::Ident>,
+    pub rent_to_own: Rc<quote::Tokens>,
     pub generics: Rc<syn::Generics>,
     pub after_state_generics: Rc<syn::Generics>,
     pub transition_state_generics: HashMap<syn::Ident, Rc<syn::Generics>>,
+    pub boxed_states: Rc<HashSet<syn::Ident>>,
+    pub stream: bool,
+    pub state_kind: Rc<quote::Ident>,
+    pub on_transition: Option<Rc<syn::Ident>>,
+    pub path_field: Option<Rc<quote::Ident>>,
+    pub events_field: Option<Rc<quote::Ident>>,
+    pub cover: Option<Rc<quote::Ident>>,
+    /// The states with `on_enter` hooks, and whether each hook is fallible.
+    pub on_enter: Rc<HashMap<syn::Ident, bool>>,
 }
 
 dummy_from_meta_item!(CodegenStateExtra);
@@ -652,24 +1199,149 @@ impl Pass for ReadyForCodegen {
                 error,
             } = extra;
 
-            let vis = Rc::new(machine.vis.clone());
+            // Inside a `module`, items need to be visible to the description's
+            // own module, which is now the parent.
+            let vis = match (&machine.module, &machine.vis) {
+                (Some(_), syn::Visibility::Inherited) => {
+                    syn::Visibility::Restricted(Box::new(syn::Path::from("super")))
+                }
+                (Some(module), syn::Visibility::Restricted(_)) => panic!(
+                    "`{}` has a restricted visibility, which would mean something else inside \
+                     its `module = \"{}\"`; make it `pub`, `pub(crate)`, or private",
+                    machine.ident,
+                    module
+                ),
+                (_, vis) => vis.clone(),
+            };
+            let vis = Rc::new(vis);
 
             let description_ident = Rc::new(machine.ident.clone());
 
-            let error_ident = states[error].ident.clone();
-            let error_ident = Rc::new(error_ident);
+            let error_ident = error.map(|e| Rc::new(states[e].ident.clone()));
 
-            let error_type = states[error].data.fields[0].ty.clone();
+            // Without an error state, the machine cannot fail.
+            let error_type = match error {
+                Some(e) => states[e].data.fields[0].ty.clone(),
+                None => syn::parse_type("::std::convert::Infallible")
+                    .expect("should parse `Infallible` type"),
+            };
             let error_type = Rc::new(error_type);
 
             let derive = Rc::new(machine.derive.clone());
 
             let machine_name = machine.ident.to_string();
 
+            let future = match machine.future {
+                Some(ref future) => future.to_string(),
+                None if machine.stream => format!("{}Stream", machine_name),
+                None => format!("{}Future", machine_name),
+            };
+            let future = Rc::new(quote::Ident::new(future));
+
+            for state in states.iter().filter(|s| !s.boxed) {
+                check_state!(
+                    state.ident,
+                    !state
+                        .data
+                        .fields
+                        .iter()
+                        .any(|f| stores_inline(&f.ty, (*future).as_ref())),
+                    "The `{}` state stores a `{}` inside of itself, which would make the `{}` \
+                     infinitely large; mark the state `boxed`, or put that field behind a `Box`",
+                    state.ident,
+                    future,
+                    future
+                );
+            }
+
+            let poll_method = |ident: &syn::Ident, poll_fn: &Option<syn::Ident>| match *poll_fn {
+                Some(ref poll_fn) => poll_fn.to_string(),
+                None => format!("poll_{}", ident.as_ref().to_snake_case()),
+            };
+
+            // Hooks belong to the machine, so a clash is reported at the
+            // later state when there is one.
+            let mut trait_methods: HashMap<String, String> = HashMap::new();
+            let hooks = machine
+                .on_transition
+                .iter()
+                .chain(machine.validate_start.iter())
+                .map(|hook| (hook.to_string(), "a hook".to_string(), None));
+            let on_enter_hooks = states.iter().filter(|s| s.on_enter.is_some()).map(|s| {
+                (
+                    format!("on_enter_{}", s.ident.as_ref().to_snake_case()),
+                    format!("the `{}` state's `on_enter` hook", s.ident),
+                    Some(&s.ident),
+                )
+            });
+            let on_exit_hooks = states.iter().filter(|s| s.on_exit).map(|s| {
+                (
+                    format!("on_exit_{}", s.ident.as_ref().to_snake_case()),
+                    format!("the `{}` state's `on_exit` hook", s.ident),
+                    Some(&s.ident),
+                )
+            });
+            let polls = states
+                .iter()
+                .filter(|s| !s.ready && !s.error && !s.yields)
+                .map(|s| {
+                    (
+                        poll_method(&s.ident, &s.poll_fn),
+                        format!("the `{}` state's poll method", s.ident),
+                        Some(&s.ident),
+                    )
+                });
+            let methods = hooks
+                .chain(on_enter_hooks)
+                .chain(on_exit_hooks)
+                .chain(polls);
+            for (method, owner, state) in methods {
+                if let Some(other) = trait_methods.insert(method.clone(), owner.clone()) {
+                    let message = format!(
+                        "The `{}` trait method name is used by both {} and {}; give one of \
+                         them a different name, for example with `poll_fn`",
+                        method, other, owner
+                    );
+                    match state {
+                        Some(state) => state_error!(state, "{}", message),
+                        None => panic!("{}", message),
+                    }
+                }
+            }
+
             let mut states_enum = machine_name.clone();
             states_enum += "States";
             let states_enum = Rc::new(quote::Ident::new(states_enum));
 
+            let mut state_kind = machine_name.clone();
+            state_kind += "State";
+            let state_kind = Rc::new(quote::Ident::new(state_kind));
+
+            let on_transition = machine.on_transition.clone().map(Rc::new);
+
+            // The generated `Future`'s optional fields follow its states, in the
+            // order `stall_info`, `record_path`, `transition_events`.
+            let path_field = if machine.record_path {
+                let index = 1 + machine.stall_info as usize;
+                Some(Rc::new(quote::Ident::new(index.to_string())))
+            } else {
+                None
+            };
+            let events_field = if machine.transition_events {
+                let index = 1 + machine.stall_info as usize + machine.record_path as usize;
+                Some(Rc::new(quote::Ident::new(index.to_string())))
+            } else {
+                None
+            };
+
+            let cover = if machine.coverage {
+                let mut cover = String::from("__smf_cover_");
+                cover += machine_name.clone().to_snake_case().as_str();
+                Some(Rc::new(quote::Ident::new(cover)))
+            } else {
+                None
+            };
+
             let mut poll_trait = String::from("Poll");
             poll_trait += &machine_name;
             let poll_trait = Rc::new(quote::Ident::new(poll_trait));
@@ -684,6 +1356,32 @@ impl Pass for ReadyForCodegen {
             smf_crate += "_state_machine_future";
             let smf_crate = Rc::new(quote::Ident::new(smf_crate));
 
+            // The path to `RentToOwn`: either the re-export from this crate, or
+            // a local equivalent generated next to the state machine.
+            let rent_to_own = if machine.inline_rent_to_own {
+                let local = quote::Ident::new(format!("{}RentToOwn", machine_name));
+                quote! { #local }
+            } else {
+                let smf_crate = &*smf_crate;
+                quote! { #smf_crate::RentToOwn }
+            };
+            let rent_to_own = Rc::new(rent_to_own);
+
+            let boxed_states: HashSet<syn::Ident> = states
+                .iter()
+                .filter(|s| s.boxed)
+                .map(|s| s.ident.clone())
+                .collect();
+            let boxed_states = Rc::new(boxed_states);
+
+            let on_enter: HashMap<syn::Ident, bool> = states
+                .iter()
+                .filter_map(|s| s.on_enter.as_ref().map(|h| (s.ident.clone(), h.fallible)))
+                .collect();
+            let on_enter = Rc::new(on_enter);
+
+            let stream = machine.stream;
+
             let states = states
                 .into_iter()
                 .map(|state| {
@@ -699,7 +1397,14 @@ impl Pass for ReadyForCodegen {
                         let states_enum = states_enum.clone();
                         let poll_trait = poll_trait.clone();
                         let futures_crate = futures_crate.clone();
-                        let smf_crate = smf_crate.clone();
+                        let rent_to_own = rent_to_own.clone();
+                        let boxed_states = boxed_states.clone();
+                        let state_kind = state_kind.clone();
+                        let on_transition = on_transition.clone();
+                        let path_field = path_field.clone();
+                        let events_field = events_field.clone();
+                        let cover = cover.clone();
+                        let on_enter = on_enter.clone();
 
                         let ident_name = state.ident.to_string();
 
@@ -707,9 +1412,8 @@ impl Pass for ReadyForCodegen {
                         after.push_str(&ident_name);
                         let after = quote::Ident::new(after);
 
-                        let mut poll_method = String::from("poll_");
-                        poll_method.push_str(&ident_name.to_snake_case());
-                        let poll_method = quote::Ident::new(poll_method);
+                        let poll_method =
+                            quote::Ident::new(poll_method(&state.ident, &state.poll_fn));
 
                         state.join(CodegenStateExtra {
                             vis,
@@ -722,10 +1426,18 @@ impl Pass for ReadyForCodegen {
                             poll_trait,
                             poll_method,
                             futures_crate,
-                            smf_crate,
+                            rent_to_own,
                             generics,
                             after_state_generics,
                             transition_state_generics,
+                            boxed_states,
+                            stream,
+                            state_kind,
+                            on_transition,
+                            path_field,
+                            events_field,
+                            cover,
+                            on_enter,
                         })
                     })
                 })
@@ -733,16 +1445,250 @@ impl Pass for ReadyForCodegen {
 
             machine.join(
                 ReadyForCodegen {
+                    vis,
                     start,
                     ready,
-                    error,
+                    error_type,
+                    future,
                     states_enum,
+                    state_kind,
                     poll_trait,
                     futures_crate,
                     smf_crate,
+                    rent_to_own,
+                    path_field,
+                    events_field,
+                    cover,
                 },
                 states,
             )
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use darling::FromDeriveInput;
+    use std::panic;
+
+    /// Run a pass that should fail validation, and get its `StateError`.
+    fn state_error<F: FnOnce() -> R, R>(pass: F) -> StateError {
+        let payload = panic::catch_unwind(panic::AssertUnwindSafe(pass))
+            .err()
+            .expect("the pass should fail");
+        *payload
+            .downcast::<StateError>()
+            .expect("the pass should fail with a `StateError`")
+    }
+
+    #[test]
+    fn unreachable_island_is_reported_once() {
+        let input = syn::parse_derive_input(
+            r#"
+            enum Island {
+                #[state_machine_future(start, transitions(Ready))]
+                Start(()),
+
+                #[state_machine_future(transitions(Y))]
+                X(()),
+
+                #[state_machine_future(transitions(X, Ready))]
+                Y(()),
+
+                #[state_machine_future(ready)]
+                Ready(()),
+            }
+            "#,
+        ).unwrap();
+        let machine = StateMachine::<Parsed>::from_derive_input(&input).unwrap();
+        let machine = ValidTransitionEdges::pass(StartReadyError::pass(machine));
+
+        let error = state_error(|| ValidPaths::pass(machine));
+        assert_eq!(error.state, syn::Ident::new("X"));
+        assert!(
+            error.message.starts_with("The `X` and `Y` states form an island"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn every_extra_error_state_is_reported() {
+        let input = syn::parse_derive_input(
+            r#"
+            enum Errors {
+                #[state_machine_future(start, transitions(Ready))]
+                Start(()),
+
+                #[state_machine_future(ready)]
+                Ready(()),
+
+                #[state_machine_future(error)]
+                A(()),
+
+                #[state_machine_future(error)]
+                B(()),
+
+                #[state_machine_future(error)]
+                C(()),
+            }
+            "#,
+        ).unwrap();
+        let machine = StateMachine::<Parsed>::from_derive_input(&input).unwrap();
+
+        let error = state_error(|| StartReadyError::pass(machine));
+        assert_eq!(error.state, syn::Ident::new("B"));
+        assert!(
+            error.message.starts_with(
+                "Only one state may be marked `error`, but `A`, `B`, and `C` are all marked \
+                 `error`; remove `error` from the extra states `B` and `C`"
+            ),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn start_state_without_transitions_is_reported() {
+        let input = syn::parse_derive_input(
+            r#"
+            enum Stuck {
+                #[state_machine_future(start, transitions())]
+                Start(()),
+
+                #[state_machine_future(ready)]
+                Ready(()),
+            }
+            "#,
+        ).unwrap();
+        let machine = StateMachine::<Parsed>::from_derive_input(&input).unwrap();
+
+        let error = state_error(|| StartReadyError::pass(machine));
+        assert_eq!(error.state, syn::Ident::new("Start"));
+        assert!(
+            error.message.starts_with(
+                "The start state `Start` has no transitions and is not the `ready` state"
+            ),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn state_without_transitions_mentions_its_dead_data() {
+        let input = syn::parse_derive_input(
+            r#"
+            enum DeadFields {
+                #[state_machine_future(start, transitions(Ready, Stuck))]
+                Start(()),
+
+                Stuck(Vec<u8>, String),
+
+                #[state_machine_future(ready)]
+                Ready(()),
+            }
+            "#,
+        ).unwrap();
+        let machine = StateMachine::<Parsed>::from_derive_input(&input).unwrap();
+
+        let error = state_error(|| StartReadyError::pass(machine));
+        assert_eq!(error.state, syn::Ident::new("Stuck"));
+        assert_eq!(
+            error.message,
+            "The `Stuck` state must have transitions, because it is neither the `ready` state \
+             nor the `error` state; add `#[state_machine_future(transitions(...))]`, otherwise \
+             the data it stores could never be consumed"
+        );
+    }
+
+    #[test]
+    fn poll_method_collision_is_reported_at_the_later_state() {
+        let input = syn::parse_derive_input(
+            r#"
+            enum Clash {
+                #[state_machine_future(start, transitions(Waiting), poll_fn = "poll_waiting")]
+                Start(()),
+
+                #[state_machine_future(transitions(Ready))]
+                Waiting(()),
+
+                #[state_machine_future(ready)]
+                Ready(()),
+            }
+            "#,
+        ).unwrap();
+        let machine = StateMachine::<Parsed>::from_derive_input(&input).unwrap();
+        let machine = AfterStateGenerics::pass(StateGenerics::pass(ValidPaths::pass(
+            ValidTransitionEdges::pass(StartReadyError::pass(machine)),
+        )));
+
+        let error = state_error(|| ReadyForCodegen::pass(machine));
+        assert_eq!(error.state, syn::Ident::new("Waiting"));
+        assert_eq!(
+            error.message,
+            "The `poll_waiting` trait method name is used by both the `Start` state's poll \
+             method and the `Waiting` state's poll method; give one of them a different name, \
+             for example with `poll_fn`"
+        );
+    }
+
+    #[test]
+    fn generic_used_twice_is_collected_once() {
+        let input = syn::parse_derive_input(
+            r#"
+            enum Twice<'a, T: 'a, U> {
+                #[state_machine_future(start, transitions(Ready))]
+                Start(T, &'a Vec<T>, Option<Box<T>>),
+
+                #[state_machine_future(ready)]
+                Ready(U),
+            }
+            "#,
+        ).unwrap();
+        let machine = StateMachine::<Parsed>::from_derive_input(&input).unwrap();
+        let machine = StateGenerics::pass(ValidPaths::pass(ValidTransitionEdges::pass(
+            StartReadyError::pass(machine),
+        )));
+
+        let (_, _, states) = machine.split();
+        let start = &states[0].extra.generics;
+        let lifetimes: Vec<_> = start
+            .lifetimes
+            .iter()
+            .map(|l| l.lifetime.ident.as_ref())
+            .collect();
+        let params: Vec<_> = start.ty_params.iter().map(|p| p.ident.as_ref()).collect();
+        assert_eq!(lifetimes, vec!["'a"]);
+        assert_eq!(params, vec!["T"]);
+    }
+
+    #[test]
+    fn associated_const_array_length_is_collected() {
+        let input = syn::parse_derive_input(
+            r#"
+            enum Buffer<T: Size, U> {
+                #[state_machine_future(start, transitions(Ready))]
+                Start([u8; T::SIZE]),
+
+                #[state_machine_future(ready)]
+                Ready(U),
+            }
+            "#,
+        ).unwrap();
+        let machine = StateMachine::<Parsed>::from_derive_input(&input).unwrap();
+        let machine = StateGenerics::pass(ValidPaths::pass(ValidTransitionEdges::pass(
+            StartReadyError::pass(machine),
+        )));
+
+        let (_, _, states) = machine.split();
+        let params: Vec<_> = states[0]
+            .extra
+            .generics
+            .ty_params
+            .iter()
+            .map(|p| p.ident.as_ref())
+            .collect();
+        assert_eq!(params, vec!["T"]);
+    }
+}
@@ -0,0 +1,154 @@
+//! Warnings about state machines that are valid, but suspicious.
+//!
+//! There is no stable way for a custom derive to emit a warning, so instead we
+//! generate a call to a `#[deprecated]` function whose note is the warning's
+//! message. Lints never fire in code expanded from a custom derive, unless the
+//! offending tokens are spanned to the user's own source, so the call is built
+//! from `proc_macro` tokens that borrow the span of the offending state's
+//! ident. This also means that `#[allow(deprecated)]` on the state machine
+//! description silences them.
+
+use ast::{State, StateMachine};
+use errors;
+use heck::SnakeCase;
+use phases;
+use proc_macro::{Delimiter, Group, Ident, Punct, Spacing, TokenStream, TokenTree};
+use quote;
+use syn;
+
+/// A single warning, attached to one of the state machine's states.
+pub struct Warning {
+    state: syn::Ident,
+    /// The state's `#[allow(...)]` attributes, which apply to its warnings as
+    /// well.
+    attrs: Vec<syn::Attribute>,
+    function: String,
+    note: String,
+}
+
+impl StateMachine<phases::ReadyForCodegen> {
+    /// Find everything that we should warn the user about.
+    pub fn warnings(&self) -> Vec<Warning> {
+        let states = self.states();
+        let mut warnings = vec![];
+
+        for s in states {
+            for duplicate in &s.transitions.duplicates {
+                warnings.push(Warning {
+                    state: s.ident.clone(),
+                    attrs: allows(s),
+                    function: format!(
+                        "{}_lists_{}_more_than_once",
+                        s.ident.as_ref().to_snake_case(),
+                        duplicate.as_ref().to_snake_case()
+                    ),
+                    note: format!(
+                        "The `{}` state lists `{}` more than once in its `transitions`; the \
+                         duplicate is ignored, so remove it",
+                        s.ident, duplicate
+                    ),
+                });
+            }
+        }
+
+        if !self.warn_ready_and_error {
+            return warnings;
+        }
+        let ready = &states[self.extra.ready].ident;
+        let error = match states.iter().find(|s| s.error) {
+            Some(error) => &error.ident,
+            None => return warnings,
+        };
+
+        warnings.extend(
+            states
+                .iter()
+                .filter(|s| s.transitions.contains(ready) && s.transitions.contains(error))
+                .map(|s| Warning {
+                    state: s.ident.clone(),
+                    attrs: allows(s),
+                    function: format!(
+                        "{}_transitions_to_both_ready_and_error",
+                        s.ident.as_ref().to_snake_case()
+                    ),
+                    note: format!(
+                        "The `{}` state lists both the ready state `{}` and the error state `{}` \
+                         in its `transitions`, but it can already fail by returning `Err` from \
+                         its poll method; make sure that transitioning to `{}` is intended, and \
+                         then add `#[allow(deprecated)]` to the `{}` state to silence this warning",
+                        s.ident, ready, error, error, s.ident
+                    ),
+                }),
+        );
+        warnings
+    }
+}
+
+/// Append the tokens that trigger each of `warnings` to `output`.
+pub fn emit(
+    machine: &StateMachine<phases::ReadyForCodegen>,
+    warnings: &[Warning],
+    input: &TokenStream,
+    output: &mut TokenStream,
+) {
+    if warnings.is_empty() {
+        return;
+    }
+
+    let mut body = TokenStream::new();
+    for warning in warnings {
+        let function = quote::Ident::new(warning.function.as_str());
+        let note = &warning.note;
+        let definition = quote! {
+            #[deprecated(note = #note)]
+            fn #function() {}
+        };
+        body.extend(parse(definition));
+
+        // Call it from a function of its own, so that the state's attributes
+        // only apply to its own warnings.
+        let attrs = &warning.attrs;
+        let caller = quote::Ident::new(format!("__smf_{}", warning.function));
+        body.extend(parse(quote! {
+            #( #attrs )*
+            fn #caller()
+        }));
+        let span = errors::variant_span(input, warning.state.as_ref());
+        let call = vec![
+            TokenTree::Ident(Ident::new(&warning.function, span)),
+            TokenTree::Group(Group::new(Delimiter::Parenthesis, TokenStream::new())),
+            TokenTree::Punct(Punct::new(';', Spacing::Alone)),
+        ];
+        body.extend(vec![TokenTree::Group(Group::new(
+            Delimiter::Brace,
+            call.into_iter().collect(),
+        ))]);
+    }
+
+    let attrs = &machine.attrs;
+    let wrapper = quote::Ident::new(format!(
+        "__smf_warnings_for_{}",
+        machine.ident.as_ref().to_snake_case()
+    ));
+    output.extend(parse(quote! {
+        #( #attrs )*
+        #[allow(dead_code)]
+        fn #wrapper()
+    }));
+    output.extend(vec![TokenTree::Group(Group::new(Delimiter::Brace, body))]);
+}
+
+fn parse(tokens: quote::Tokens) -> TokenStream {
+    tokens
+        .parse()
+        .expect("should parse warning source into tokens")
+}
+
+fn allows(state: &State<phases::ReadyForCodegen>) -> Vec<syn::Attribute> {
+    state
+        .attrs
+        .iter()
+        .filter(|a| a.name() == "allow")
+        .cloned()
+        .collect()
+}
@@ -21,36 +21,6 @@ mod no_start_state {
      */
 }
 
-mod no_error_state {
-    /*!
-    ```compile_fail
-    #[macro_use]
-    extern crate state_machine_future;
-    extern crate futures;
-    use futures::*;
-    fn main() {}
-    impl PollMachine for Machine {
-        fn poll_start<'a>(
-            _: &'a mut state_machine_future::RentToOwn<'a, Start>
-        ) -> Poll<AfterStart, usize> {
-            unimplemented!()
-        }
-    }
-
-
-    #[derive(StateMachineFuture)]
-    pub enum Machine {
-        #[state_machine_future(start)]
-        #[state_machine_future(transitions(Ready))]
-        Start,
-
-        #[state_machine_future(ready)]
-        Ready(usize),
-    }
-    ```
-     */
-}
-
 mod no_ready_state {
     /*!
     ```compile_fail
@@ -211,12 +181,16 @@ mod no_transitions_on_non_ready_or_error_state {
 
     #[derive(StateMachineFuture)]
     pub enum Machine {
-        #[state_machine_future(start)]
+        #[state_machine_future(start, transitions(Middle))]
         Start,
 
+        Middle,
+
         #[state_machine_future(ready)]
-        #[state_machine_future(error)]
         Ready(usize),
+
+        #[state_machine_future(error)]
+        Error(usize),
     }
     ```
      */
@@ -598,3 +572,1073 @@ mod reachable_state_without_path_to_ready_or_error {
     ```
      */
 }
+
+mod yields_state_without_stream {
+    /*!
+    ```compile_fail
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+    use futures::*;
+    fn main() {}
+    impl PollMachine for Machine {
+        fn poll_start<'a>(
+            _: &'a mut state_machine_future::RentToOwn<'a, Start>
+        ) -> Poll<AfterStart, usize> {
+            unimplemented!()
+        }
+    }
+
+    #[derive(StateMachineFuture)]
+    pub enum Machine {
+        #[state_machine_future(start)]
+        #[state_machine_future(transitions(Yield, Ready))]
+        Start,
+
+        #[state_machine_future(yields, transitions(Start))]
+        Yield(usize, Start),
+
+        #[state_machine_future(ready)]
+        Ready(usize),
+
+        #[state_machine_future(error)]
+        Error(usize),
+    }
+    ```
+     */
+}
+
+mod stream_without_yields_state {
+    /*!
+    ```compile_fail
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+    use futures::*;
+    fn main() {}
+    impl PollMachine for Machine {
+        fn poll_start<'a>(
+            _: &'a mut state_machine_future::RentToOwn<'a, Start>
+        ) -> Poll<AfterStart, usize> {
+            unimplemented!()
+        }
+    }
+
+    #[derive(StateMachineFuture)]
+    #[state_machine_future(stream)]
+    pub enum Machine {
+        #[state_machine_future(start)]
+        #[state_machine_future(transitions(Ready))]
+        Start,
+
+        #[state_machine_future(ready)]
+        Ready(usize),
+
+        #[state_machine_future(error)]
+        Error(usize),
+    }
+    ```
+     */
+}
+
+mod start_and_ready_with_transitions {
+    /*!
+    ```compile_fail
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+    use futures::*;
+    fn main() {}
+    impl PollMachine for Machine {
+        fn poll_done<'a>(
+            _: &'a mut state_machine_future::RentToOwn<'a, Done>
+        ) -> Poll<AfterDone, usize> {
+            unimplemented!()
+        }
+    }
+
+    #[derive(StateMachineFuture)]
+    pub enum Machine {
+        #[state_machine_future(start, ready)]
+        #[state_machine_future(transitions(Error))]
+        Done(usize),
+
+        #[state_machine_future(error)]
+        Error(usize),
+    }
+    ```
+     */
+}
+
+mod unused_after_enum {
+    /*!
+    ```compile_fail
+    #![deny(unused_must_use)]
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+    use futures::*;
+    use state_machine_future::RentToOwn;
+    fn main() {}
+
+    #[derive(StateMachineFuture)]
+    pub enum Machine {
+        #[state_machine_future(start, transitions(Ready))]
+        Start,
+
+        #[state_machine_future(ready)]
+        Ready(usize),
+
+        #[state_machine_future(error)]
+        Error(usize),
+    }
+
+    impl PollMachine for Machine {
+        fn poll_start<'a>(_: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, usize> {
+            unimplemented!()
+        }
+    }
+
+    fn make_transition() -> AfterStart {
+        Ready(1).into()
+    }
+
+    fn drops_transition() {
+        make_transition();
+    }
+    ```
+     */
+}
+
+mod unused_state_machine_future {
+    /*!
+    ```compile_fail
+    #![deny(unused_must_use)]
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+    use futures::*;
+    use state_machine_future::RentToOwn;
+    fn main() {}
+
+    #[derive(StateMachineFuture)]
+    pub enum Machine {
+        #[state_machine_future(start, transitions(Ready))]
+        Start,
+
+        #[state_machine_future(ready)]
+        Ready(usize),
+
+        #[state_machine_future(error)]
+        Error(usize),
+    }
+
+    impl PollMachine for Machine {
+        fn poll_start<'a>(_: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, usize> {
+            unimplemented!()
+        }
+    }
+
+    fn drops_future() {
+        Machine::start();
+    }
+    ```
+     */
+}
+
+mod empty_transitions_on_non_ready_or_error_state {
+    /*!
+    ```compile_fail
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+    use futures::*;
+    fn main() {}
+    impl PollMachine for Machine {
+        fn poll_start<'a>(
+            _: &'a mut state_machine_future::RentToOwn<'a, Start>
+        ) -> Poll<AfterStart, usize> {
+            unimplemented!()
+        }
+    }
+
+    #[derive(StateMachineFuture)]
+    pub enum Machine {
+        #[state_machine_future(start, transitions(Middle))]
+        Start(usize),
+
+        #[state_machine_future(transitions())]
+        Middle(usize),
+
+        #[state_machine_future(ready)]
+        Ready(usize),
+
+        #[state_machine_future(error)]
+        Error(usize),
+    }
+    ```
+     */
+}
+
+mod state_named_like_state_kind {
+    /*!
+    ```compile_fail
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+    fn main() {}
+
+    #[derive(StateMachineFuture)]
+    #[state_machine_future(state_enum)]
+    pub enum Machine {
+        #[state_machine_future(start, ready)]
+        MachineState(()),
+    }
+    ```
+     */
+}
+
+mod transitions_to_both_ready_and_error_warns {
+    /*!
+    ```compile_fail
+    #![deny(deprecated)]
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+    use futures::*;
+    fn main() {}
+    impl PollMachine for Machine {
+        fn poll_start<'a>(
+            _: &'a mut state_machine_future::RentToOwn<'a, Start>
+        ) -> Poll<AfterStart, usize> {
+            unimplemented!()
+        }
+    }
+
+    #[derive(StateMachineFuture)]
+    #[state_machine_future(warn_ready_and_error)]
+    pub enum Machine {
+        #[state_machine_future(start)]
+        #[state_machine_future(transitions(Ready, Error))]
+        Start,
+
+        #[state_machine_future(ready)]
+        Ready(usize),
+
+        #[state_machine_future(error)]
+        Error(usize),
+    }
+    ```
+     */
+}
+
+mod assert_send_with_rc_field {
+    /*!
+    ```compile_fail
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+    use futures::*;
+    use std::rc::Rc;
+    fn main() {}
+    impl PollMachine for Machine {
+        fn poll_start<'a>(
+            _: &'a mut state_machine_future::RentToOwn<'a, Start>
+        ) -> Poll<AfterStart, usize> {
+            unimplemented!()
+        }
+    }
+
+    #[derive(StateMachineFuture)]
+    #[state_machine_future(assert_send)]
+    pub enum Machine {
+        #[state_machine_future(start, transitions(Ready))]
+        Start(Rc<usize>),
+
+        #[state_machine_future(ready)]
+        Ready(usize),
+
+        #[state_machine_future(error)]
+        Error(usize),
+    }
+    ```
+     */
+}
+
+mod error_type_on_ready_state {
+    /*!
+    ```compile_fail
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+    use futures::*;
+    fn main() {}
+    impl PollMachine for Machine {
+        fn poll_start<'a>(
+            _: &'a mut state_machine_future::RentToOwn<'a, Start>
+        ) -> Poll<AfterStart, usize> {
+            unimplemented!()
+        }
+    }
+
+    #[derive(StateMachineFuture)]
+    pub enum Machine {
+        #[state_machine_future(start, transitions(Ready))]
+        Start,
+
+        #[state_machine_future(ready, error_type = "u8")]
+        Ready(usize),
+
+        #[state_machine_future(error)]
+        Error(usize),
+    }
+    ```
+     */
+}
+
+mod state_allow_does_not_leak_to_siblings {
+    /*!
+    ```compile_fail
+    #![deny(non_snake_case)]
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+    use futures::*;
+    fn main() {}
+    impl PollMachine for Machine {
+        fn poll_start<'a>(
+            _: &'a mut state_machine_future::RentToOwn<'a, Start>
+        ) -> Poll<AfterStart, usize> {
+            unimplemented!()
+        }
+
+        fn poll_sibling<'a>(
+            _: &'a mut state_machine_future::RentToOwn<'a, Sibling>
+        ) -> Poll<AfterSibling, usize> {
+            unimplemented!()
+        }
+    }
+
+    #[derive(StateMachineFuture)]
+    pub enum Machine {
+        #[state_machine_future(start, transitions(Sibling))]
+        #[allow(non_snake_case)]
+        Start { ShoutyField: usize },
+
+        #[state_machine_future(transitions(Ready))]
+        Sibling { AlsoShouty: usize },
+
+        #[state_machine_future(ready)]
+        Ready(usize),
+
+        #[state_machine_future(error)]
+        Error(usize),
+    }
+    ```
+     */
+}
+
+mod finish_on_ready_state {
+    /*!
+    ```compile_fail
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+    fn main() {}
+
+    #[derive(StateMachineFuture)]
+    pub enum Machine {
+        #[state_machine_future(start, ready, finish)]
+        Ready(usize),
+
+        #[state_machine_future(error)]
+        Error(usize),
+    }
+    ```
+     */
+}
+
+mod state_stores_own_future_inline {
+    /*!
+    ```compile_fail
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+    use futures::*;
+    fn main() {}
+    impl PollMachine for Machine {
+        fn poll_start<'a>(
+            _: &'a mut state_machine_future::RentToOwn<'a, Start>
+        ) -> Poll<AfterStart, usize> {
+            unimplemented!()
+        }
+    }
+
+    #[derive(StateMachineFuture)]
+    pub enum Machine {
+        #[state_machine_future(start, transitions(Ready))]
+        Start(usize, Option<MachineFuture>),
+
+        #[state_machine_future(ready)]
+        Ready(usize),
+
+        #[state_machine_future(error)]
+        Error(usize),
+    }
+    ```
+     */
+}
+
+mod entry_on_ready {
+    /*!
+    ```compile_fail
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+
+    use futures::Poll;
+    use state_machine_future::RentToOwn;
+
+    #[derive(StateMachineFuture)]
+    pub enum EntryReady {
+        #[state_machine_future(start, transitions(Ready))]
+        Start(()),
+
+        #[state_machine_future(ready, entry)]
+        Ready(()),
+
+        #[state_machine_future(error)]
+        Error(()),
+    }
+
+    impl PollEntryReady for EntryReady {
+        fn poll_start<'a>(_: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, ()> {
+            transition!(Ready(()))
+        }
+    }
+
+    fn main() {}
+    ```
+     */
+}
+
+mod dead_state_fields {
+    /*!
+    ```compile_fail
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+
+    use futures::Poll;
+    use state_machine_future::RentToOwn;
+
+    #[derive(StateMachineFuture)]
+    pub enum DeadFields {
+        #[state_machine_future(start, transitions(Ready, Stuck))]
+        Start(()),
+
+        Stuck(Vec<u8>, String),
+
+        #[state_machine_future(ready)]
+        Ready(()),
+
+        #[state_machine_future(error)]
+        Error(()),
+    }
+
+    impl PollDeadFields for DeadFields {
+        fn poll_start<'a>(_: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, ()> {
+            transition!(Stuck(vec![], String::new()))
+        }
+
+        fn poll_stuck<'a>(_: &'a mut RentToOwn<'a, Stuck>) -> Poll<AfterStuck, ()> {
+            transition!(Ready(()))
+        }
+    }
+
+    fn main() {}
+    ```
+     */
+}
+
+mod poll_fn_collision {
+    /*!
+    ```compile_fail
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+
+    use futures::Poll;
+    use state_machine_future::RentToOwn;
+
+    #[derive(StateMachineFuture)]
+    pub enum PollFnCollision {
+        #[state_machine_future(start, transitions(Other), poll_fn = "poll_other")]
+        Start(()),
+
+        #[state_machine_future(transitions(Ready))]
+        Other(()),
+
+        #[state_machine_future(ready)]
+        Ready(()),
+
+        #[state_machine_future(error)]
+        Error(()),
+    }
+
+    impl PollPollFnCollision for PollFnCollision {
+        fn poll_other<'a>(_: &'a mut RentToOwn<'a, Other>) -> Poll<AfterOther, ()> {
+            transition!(Ready(()))
+        }
+    }
+
+    fn main() {}
+    ```
+     */
+}
+
+mod no_start_loops {
+    /*!
+    ```compile_fail
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+
+    use futures::Poll;
+    use state_machine_future::RentToOwn;
+
+    #[derive(StateMachineFuture)]
+    #[state_machine_future(no_start_loops)]
+    pub enum StartLoop {
+        #[state_machine_future(start, transitions(Check))]
+        Attempt(()),
+
+        #[state_machine_future(transitions(Attempt, Ready))]
+        Check(()),
+
+        #[state_machine_future(ready)]
+        Ready(()),
+
+        #[state_machine_future(error)]
+        Error(()),
+    }
+
+    impl PollStartLoop for StartLoop {
+        fn poll_attempt<'a>(_: &'a mut RentToOwn<'a, Attempt>) -> Poll<AfterAttempt, ()> {
+            transition!(Check(()))
+        }
+
+        fn poll_check<'a>(_: &'a mut RentToOwn<'a, Check>) -> Poll<AfterCheck, ()> {
+            transition!(Ready(()))
+        }
+    }
+
+    fn main() {}
+    ```
+     */
+}
+
+mod unreachable_island {
+    /*!
+    ```compile_fail
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+
+    use futures::Poll;
+    use state_machine_future::RentToOwn;
+
+    #[derive(StateMachineFuture)]
+    pub enum Island {
+        #[state_machine_future(start, transitions(Ready))]
+        Start(()),
+
+        #[state_machine_future(transitions(Y))]
+        X(()),
+
+        #[state_machine_future(transitions(X, Ready))]
+        Y(()),
+
+        #[state_machine_future(ready)]
+        Ready(()),
+
+        #[state_machine_future(error)]
+        Error(()),
+    }
+
+    impl PollIsland for Island {
+        fn poll_start<'a>(_: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, ()> {
+            transition!(Ready(()))
+        }
+
+        fn poll_x<'a>(_: &'a mut RentToOwn<'a, X>) -> Poll<AfterX, ()> {
+            transition!(Y(()))
+        }
+
+        fn poll_y<'a>(_: &'a mut RentToOwn<'a, Y>) -> Poll<AfterY, ()> {
+            transition!(Ready(()))
+        }
+    }
+
+    fn main() {}
+    ```
+     */
+}
+
+mod sync_stream {
+    /*!
+    ```compile_fail
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+
+    use futures::Poll;
+    use state_machine_future::RentToOwn;
+    use std::convert::Infallible;
+
+    #[derive(StateMachineFuture)]
+    #[state_machine_future(stream, sync)]
+    pub enum Tokens {
+        #[state_machine_future(start, transitions(Token, Done))]
+        Scanning(()),
+
+        #[state_machine_future(yields, transitions(Scanning))]
+        Token(u8, Scanning),
+
+        #[state_machine_future(ready)]
+        Done(()),
+    }
+
+    impl PollTokens for Tokens {
+        fn poll_scanning<'a>(_: &'a mut RentToOwn<'a, Scanning>) -> Poll<AfterScanning, Infallible> {
+            transition!(Done(()))
+        }
+    }
+
+    fn main() {}
+    ```
+     */
+}
+
+mod ready_and_error_state {
+    /*!
+    ```compile_fail
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+
+    use futures::Poll;
+    use state_machine_future::RentToOwn;
+
+    #[derive(StateMachineFuture)]
+    pub enum Machine {
+        #[state_machine_future(start, transitions(Done))]
+        Start(()),
+
+        #[state_machine_future(ready, error)]
+        Done(()),
+    }
+
+    impl PollMachine for Machine {
+        fn poll_start<'a>(_: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, ()> {
+            transition!(Done(()))
+        }
+    }
+
+    fn main() {}
+    ```
+     */
+}
+
+mod stay_on_ready {
+    /*!
+    ```compile_fail
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+
+    use futures::Poll;
+    use state_machine_future::RentToOwn;
+    use std::convert::Infallible;
+
+    #[derive(StateMachineFuture)]
+    pub enum Machine {
+        #[state_machine_future(start, transitions(Ready))]
+        Start(()),
+
+        #[state_machine_future(ready, stay)]
+        Ready(()),
+    }
+
+    impl PollMachine for Machine {
+        fn poll_start<'a>(_: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, Infallible> {
+            transition!(Ready(()))
+        }
+    }
+
+    fn main() {}
+    ```
+     */
+}
+
+mod repoll_with_backoff {
+    /*!
+    ```compile_fail
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+    use futures::*;
+    fn main() {}
+    impl PollMachine for Machine {
+        fn poll_start<'a>(
+            _: &'a mut state_machine_future::RentToOwn<'a, Start>
+        ) -> Poll<AfterStart, ()> {
+            unimplemented!()
+        }
+    }
+
+    #[derive(StateMachineFuture)]
+    pub enum Machine {
+        #[state_machine_future(start, transitions(Ready), repoll(max = 3, backoff_ms = 100))]
+        Start,
+
+        #[state_machine_future(ready)]
+        Ready(()),
+
+        #[state_machine_future(error)]
+        Error(()),
+    }
+    ```
+     */
+}
+
+mod start_state_without_way_out {
+    /*!
+    ```compile_fail
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+
+    use futures::Poll;
+    use state_machine_future::RentToOwn;
+
+    #[derive(StateMachineFuture)]
+    pub enum Machine {
+        #[state_machine_future(start, transitions())]
+        Start(usize),
+
+        #[state_machine_future(ready)]
+        Ready(usize),
+
+        #[state_machine_future(error)]
+        Error(usize),
+    }
+
+    impl PollMachine for Machine {
+        fn poll_start<'a>(_: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, usize> {
+            unimplemented!()
+        }
+    }
+
+    fn main() {}
+    ```
+     */
+}
+
+mod duplicate_transition_warns {
+    /*!
+    ```compile_fail
+    #![deny(deprecated)]
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+    use futures::*;
+    fn main() {}
+    impl PollMachine for Machine {
+        fn poll_start<'a>(
+            _: &'a mut state_machine_future::RentToOwn<'a, Start>
+        ) -> Poll<AfterStart, ()> {
+            unimplemented!()
+        }
+    }
+
+    #[derive(StateMachineFuture)]
+    pub enum Machine {
+        #[state_machine_future(start, transitions(Ready, Ready))]
+        Start,
+
+        #[state_machine_future(ready)]
+        Ready(()),
+
+        #[state_machine_future(error)]
+        Error(()),
+    }
+    ```
+     */
+}
+
+mod larger_than_max_size {
+    /*!
+    ```compile_fail
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+
+    use futures::Poll;
+    use state_machine_future::RentToOwn;
+
+    #[derive(StateMachineFuture)]
+    #[state_machine_future(max_size = 16)]
+    pub enum Machine {
+        #[state_machine_future(start, transitions(Ready))]
+        Start([u8; 64]),
+
+        #[state_machine_future(ready)]
+        Ready(()),
+
+        #[state_machine_future(error)]
+        Error(()),
+    }
+
+    impl PollMachine for Machine {
+        fn poll_start<'a>(_: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, ()> {
+            unimplemented!()
+        }
+    }
+
+    # fn main() {}
+    ```
+     */
+}
+
+mod poll_trait_bound_not_met {
+    /*!
+    ```compile_fail
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+
+    use futures::Poll;
+    use state_machine_future::RentToOwn;
+    use std::rc::Rc;
+
+    #[derive(StateMachineFuture)]
+    #[state_machine_future(poll_trait_bound = "Send")]
+    pub enum Machine {
+        #[state_machine_future(start, transitions(Ready))]
+        Start(Rc<u32>),
+
+        #[state_machine_future(ready)]
+        Ready(()),
+
+        #[state_machine_future(error)]
+        Error(()),
+    }
+
+    impl PollMachine for Machine {
+        fn poll_start<'a>(_: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, ()> {
+            unimplemented!()
+        }
+    }
+
+    # fn main() {}
+    ```
+     */
+}
+
+mod bare_transitions {
+    /*!
+    ```compile_fail
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+
+    use futures::Poll;
+    use state_machine_future::RentToOwn;
+
+    #[derive(StateMachineFuture)]
+    pub enum Machine {
+        #[state_machine_future(start, transitions)]
+        Start(()),
+
+        #[state_machine_future(ready)]
+        Ready(()),
+
+        #[state_machine_future(error)]
+        Error(()),
+    }
+
+    impl PollMachine for Machine {
+        fn poll_start<'a>(_: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, ()> {
+            transition!(Ready(()))
+        }
+    }
+
+    # fn main() {}
+    ```
+     */
+}
+
+mod transitions_with_string_value {
+    /*!
+    ```compile_fail
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+
+    use futures::Poll;
+    use state_machine_future::RentToOwn;
+
+    #[derive(StateMachineFuture)]
+    pub enum Machine {
+        #[state_machine_future(start, transitions = "Ready")]
+        Start(()),
+
+        #[state_machine_future(ready)]
+        Ready(()),
+
+        #[state_machine_future(error)]
+        Error(()),
+    }
+
+    impl PollMachine for Machine {
+        fn poll_start<'a>(_: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, ()> {
+            transition!(Ready(()))
+        }
+    }
+
+    # fn main() {}
+    ```
+     */
+}
+
+mod transitions_with_path_value {
+    /*!
+    ```compile_fail
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+
+    use futures::Poll;
+    use state_machine_future::RentToOwn;
+
+    #[derive(StateMachineFuture)]
+    pub enum Machine {
+        #[state_machine_future(start, transitions = Ready)]
+        Start(()),
+
+        #[state_machine_future(ready)]
+        Ready(()),
+
+        #[state_machine_future(error)]
+        Error(()),
+    }
+
+    impl PollMachine for Machine {
+        fn poll_start<'a>(_: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, ()> {
+            transition!(Ready(()))
+        }
+    }
+
+    # fn main() {}
+    ```
+     */
+}
+
+mod pub_crate_machine_states_are_not_pub {
+    /*!
+    ```compile_fail
+    #![deny(private_interfaces)]
+
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+
+    mod machines {
+        #[derive(StateMachineFuture)]
+        pub(crate) enum Machine {
+            #[state_machine_future(start, ready)]
+            Start(()),
+
+            #[state_machine_future(error)]
+            Error(()),
+        }
+    }
+
+    // The generated state is only `pub(crate)`, like the description.
+    pub fn leak(_: machines::Start) {}
+
+    # fn main() {}
+    ```
+     */
+}
+
+mod transition_events_on_stream {
+    /*!
+    ```compile_fail
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+
+    use futures::Poll;
+    use state_machine_future::RentToOwn;
+    use std::convert::Infallible;
+
+    #[derive(StateMachineFuture)]
+    #[state_machine_future(stream, transition_events)]
+    pub enum Tokens {
+        #[state_machine_future(start, transitions(Token, Done))]
+        Scanning(()),
+
+        #[state_machine_future(yields, transitions(Scanning))]
+        Token(u8, Scanning),
+
+        #[state_machine_future(ready)]
+        Done(()),
+    }
+
+    impl PollTokens for Tokens {
+        fn poll_scanning<'a>(_: &'a mut RentToOwn<'a, Scanning>) -> Poll<AfterScanning, Infallible> {
+            transition!(Done(()))
+        }
+    }
+
+    fn main() {}
+    ```
+     */
+}
+
+mod ready_item_does_not_meet_ready_bound {
+    /*!
+    ```compile_fail
+    #[macro_use]
+    extern crate state_machine_future;
+    extern crate futures;
+
+    use futures::Poll;
+    use state_machine_future::RentToOwn;
+    use std::rc::Rc;
+
+    #[derive(StateMachineFuture)]
+    #[state_machine_future(ready_bound = "Send")]
+    pub enum Machine {
+        #[state_machine_future(start, transitions(Ready))]
+        Start(()),
+
+        #[state_machine_future(ready)]
+        Ready(Rc<u32>),
+
+        #[state_machine_future(error)]
+        Error(()),
+    }
+
+    impl PollMachine for Machine {
+        fn poll_start<'a>(_: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, ()> {
+            unimplemented!()
+        }
+    }
+
+    # fn main() {}
+    ```
+     */
+}
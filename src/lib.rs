@@ -14,6 +14,7 @@ boilerplate for you.
 * [Attributes](#attributes)
 * [Macro](#macro)
 * [Features](#features)
+* [Minimum Rust version](#minimum-rust-version)
 * [License](#license)
 * [Contribution](#contribution)
 
@@ -41,11 +42,11 @@ transitions between them, and then the custom derive generates:
 * A type for the whole state machine that implements `Future`.
 
 * A concrete `start` method that constructs the state machine `Future` for you,
-initialized to its start state.
+  initialized to its start state.
 
 * A state transition polling trait, with a `poll_zee_choo` method for each
-non-final state `ZeeChoo`. This trait describes the state machine's valid
-transitions, and its methods are called by `Future::poll`.
+  non-final state `ZeeChoo`. This trait describes the state machine's valid
+  transitions, and its methods are called by `Future::poll`.
 
 Then, all *we* need to do is implement the generated state transition polling
 trait.
@@ -56,10 +57,10 @@ some footguns that can arise when writing state machines:
 * Every state is reachable from the start state: *there are no useless states.*
 
 * *There are no states which cannot reach a final state*. These states would
-otherwise lead to infinite loops.
+  otherwise lead to infinite loops.
 
 * *All state transitions are valid.* Attempting to make an invalid state
-transition fails to type check, thanks to the generated typestates.
+  transition fails to type check, thanks to the generated typestates.
 
 ## Guide
 
@@ -74,8 +75,10 @@ enum MyStateMachine {
 ```
 
 There must be one **start** state, which is the initial state upon construction;
-one **ready** state, which corresponds to `Future::Item`; and one **error**
-state, which corresponds to `Future::Error`.
+one **ready** state, which corresponds to `Future::Item`; and at most one
+**error** state, which corresponds to `Future::Error`. A state machine without
+an **error** state cannot fail, and its `Future::Error` is
+`std::convert::Infallible`.
 
 ```ignore
 #[derive(StateMachineFuture)]
@@ -127,10 +130,13 @@ us.
 For each state, the custom derive creates:
 
 * A typestate for the state. The type's name matches the variant name, for
-example the `Intermediate` state variant's typestate is also named `Intermediate`.
-The kind of struct type generated matches the variant kind: a unit-style variant
-results in a unit struct, a tuple-style variant results in a tuple struct, and a
-struct-style variant results in a normal struct with fields.
+  example the `Intermediate` state variant's typestate is also named `Intermediate`.
+  The kind of struct type generated matches the variant kind: a unit-style variant
+  results in a unit struct, a tuple-style variant results in a tuple struct, and a
+  struct-style variant results in a normal struct with fields.
+  The typestate and its fields have the same visibility as the description
+  `enum`, as does every other generated item, so a `pub(crate)` description gets
+  `pub(crate)` typestates, a `pub(crate)` `Future`, and so on.
 
 | State `enum` Variant                              | Generated Typestate            |
 | ------------------------------------------------- | ------------------------------ |
@@ -139,9 +145,9 @@ struct-style variant results in a normal struct with fields.
 | `enum StateMachine { MyState { x: usize }, ... }` | `struct MyState { x: usize };` |
 
 * An `enum` for the possible states that can come after this state. This `enum`
-is named `AfterX` where `X` is the state's name. There is also a `From<Y>`
-implementation for each `Y` state that can be transitioned to after `X`. For
-example, the `Intermediate` state would get:
+  is named `AfterX` where `X` is the state's name. There is also a `From<Y>`
+  implementation for each `Y` state that can be transitioned to after `X`. For
+  example, the `Intermediate` state would get:
 
 ```ignore
 enum AfterIntermediate {
@@ -156,24 +162,33 @@ impl From<Start> for AfterIntermediate {
 impl From<Ready> for AfterIntermediate {
     // ...
 }
+
+impl AfterIntermediate {
+    fn into_start() -> AfterIntermediate { /* ... */ }
+    fn into_ready(arg0: MyItem) -> AfterIntermediate { /* ... */ }
+}
 ```
 
+  The `into_y` constructors take the same parameters as the `Y` state's fields,
+  so that a polling trait method can return, for example,
+  `Ok(Async::Ready(AfterIntermediate::into_ready(item)))`.
+
 Next, for the state machine as a whole, the custom derive generates:
 
 * A state machine `Future` type, which is essentially an `enum` of all the
-different typestates. This type is named `BlahFuture` where `Blah` is the name
-of the state machine description `enum`. In this example, where the state
-machine description is named `MyStateMachine`, the generated state machine
-future type would be named `MyStateMachineFuture`.
+  different typestates. This type is named `BlahFuture` where `Blah` is the name
+  of the state machine description `enum`. In this example, where the state
+  machine description is named `MyStateMachine`, the generated state machine
+  future type would be named `MyStateMachineFuture`.
 
 * A polling trait, `PollBordle` where `Bordle` is this state machine
-description's name. For each non-final state `TootWasabi`, this trait has a
-method, `poll_toot_wasabi`, which is like `Future::poll` but specialized to the
-current state. Each method takes conditional ownership of its state (via
-[`RentToOwn`][rent_to_own]) and returns a `futures::Poll<AfterThisState, Error>`
-where `Error` is the state machine's error type. This signature *does not allow
-invalid state transitions*, which makes attempting an illegal state transition
-fail to type check. Here is the `MyStateMachine`'s polling trait, for example:
+  description's name. For each non-final state `TootWasabi`, this trait has a
+  method, `poll_toot_wasabi`, which is like `Future::poll` but specialized to the
+  current state. Each method takes conditional ownership of its state (via
+  [`RentToOwn`][rent_to_own]) and returns a `futures::Poll<AfterThisState, Error>`
+  where `Error` is the state machine's error type. This signature *does not allow
+  invalid state transitions*, which makes attempting an illegal state transition
+  fail to type check. Here is the `MyStateMachine`'s polling trait, for example:
 
 ```ignore
 trait PollMyStateMachine {
@@ -188,8 +203,8 @@ trait PollMyStateMachine {
 ```
 
 * An implementation of `Future` for that type. This implementation dispatches to
-the appropriate polling trait method depending on what state the future is
-in:
+  the appropriate polling trait method depending on what state the future is
+  in:
 
   * If the `Future` is in the `Start` state, then it uses `<MyStateMachine as
     PollMyStateMachine>::poll_start`.
@@ -200,9 +215,9 @@ in:
   * Etc...
 
 * A concrete `start` method for the description type (so `MyStateMachine::start`
-in this example) which constructs a new state machine `Future` type in its
-**start** state for you. This method has a parameter for each field in the
-**start** state variant.
+  in this example) which constructs a new state machine `Future` type in its
+  **start** state for you. This method has a parameter for each field in the
+  **start** state variant.
 
 | Start `enum` Variant            | Generated `start` Method                                            |
 | ------------------------------- | ------------------------------------------------------------------- |
@@ -210,6 +225,10 @@ in this example) which constructs a new state machine `Future` type in its
 | `MyStart(bool, usize),`         | `fn start(arg0: bool, arg1: usize) -> MyStateMachineFuture { ... }` |
 | `MyStart { x: char, y: bool },` | `fn start(x: char, y: bool) -> MyStateMachineFuture { ... }`        |
 
+Attributes can ask for more: a `BlahState` `enum` that mirrors the states,
+introspection items on the description type, and helper methods on the `Future`.
+See [Attributes](#attributes).
+
 Given all those generated types and traits, all we have to do is `impl PollBlah
 for Blah` for our state machine `Blah`.
 
@@ -410,30 +429,379 @@ fn spawn_game(handle: TokioHandle) {
 This is a list of all of the attributes used by `state_machine_future`:
 
 * `#[derive(StateMachineFuture)]`: Placed on an `enum` that describes a state
-machine.
+  machine.
 
 * `#[state_machine_future(derive(Clone, Debug, ...))]`: Placed on the `enum`
-that describes the state machine. This attribute describes which
-`#[derive(...)]`s to place on the generated `Future` type.
+  that describes the state machine. This attribute describes which
+  `#[derive(...)]`s to place on the generated `Future` type, the typestate for
+  each state, and the `After*` transition `enum`s.
 
 * `#[state_machine_future(start)]`: Used on a variant of the state machine
-description `enum`. There must be exactly one variant with this attribute. This
-describes the initial starting state. The generated `start` method has a
-parameter for each field in this variant.
+  description `enum`. There must be exactly one variant with this attribute. This
+  describes the initial starting state. The generated `start` method has a
+  parameter for each field in this variant.
 
 * `#[state_machine_future(ready)]`: Used on a variant of the state machine
-description `enum`. There must be exactly one variant with this attribute. It
-must be a tuple-style variant with one field, for example `Ready(MyItemType)`.
-The generated `Future` implementation uses the field's type as `Future::Item`.
+  description `enum`. There must be exactly one variant with this attribute. It
+  must be a tuple-style variant with one field, for example `Ready(MyItemType)`.
+  The generated `Future` implementation uses the field's type as `Future::Item`.
 
 * `#[state_machine_future(error)]`: Used on a variant of the state machine
-description `enum`. There must be exactly one variant with this attribute. It
-must be a tuple-style variant with one field, for example `Error(MyError)`.  The
-generated `Future` implementation uses the field's type as `Future::Error`.
+  description `enum`. There may be at most one variant with this attribute. It
+  must be a tuple-style variant with one field, for example `Error(MyError)`, and
+  must not also be the **ready** state. The generated `Future` implementation uses
+  the field's type as `Future::Error`. If
+  there is no variant with this attribute, `Future::Error` is
+  `std::convert::Infallible`.
 
 * `#[state_machine_future(transitions(OtherState, AnotherState, ...))]`: Used on
-a variant of the state machine description `enum`. Describes the states that
-this one can transition to.
+  a variant of the state machine description `enum`. Describes the states that
+  this one can transition to.
+
+  Listing the same state more than once warns, and the duplicates are ignored.
+
+  Each state may be given a label, as in `transitions(Retry = "rare", Done)`.
+  Labels do not change how the state machine runs; they only label the
+  transition's edge in the generated `MERMAID` diagram.
+
+  With `warn_ready_and_error` (see below), a state that lists both the
+  **ready** and **error** states gets a warning.
+
+  A state may be placed behind `#[cfg(...)]`, in which case any `transitions`
+  that mention it must be placed behind a matching `#[cfg_attr(...)]`:
+
+  ```ignore
+  #[state_machine_future(start)]
+  #[cfg_attr(feature = "extra", state_machine_future(transitions(Extra)))]
+  #[cfg_attr(not(feature = "extra"), state_machine_future(transitions(Ready)))]
+  Start,
+
+  #[cfg(feature = "extra")]
+  #[state_machine_future(transitions(Ready))]
+  Extra,
+  ```
+
+* `#[state_machine_future(finish)]`: Used on a variant of the state machine
+  description `enum`. Shorthand for listing the **ready** state in this state's
+  `transitions`, and may be combined with them. A state with `finish` does not
+  need a `transitions` attribute at all.
+
+* `#[state_machine_future(stay)]`: Used on a variant of the state machine
+  description `enum` that has a polling trait method. Adds a `Stay` variant to
+  the state's `After*` `enum`. When the polling method returns
+  `Ok(Async::Ready(AfterBlah::Stay))`, the state machine keeps the state,
+  including any changes the method made to it through the `RentToOwn`, and polls
+  it again straight away. Staying is not a transition, so it does not call
+  `on_transition` or `on_enter` hooks, and the state does not need to list itself
+  in `transitions`.
+
+* `#[state_machine_future(on_enter)]`: Used on a variant of the state machine
+  description `enum` that is not the **error** state. Adds an
+  `on_enter_blah(state: &mut Blah)` method to the polling trait, which is called
+  whenever a transition enters the state, before the state is polled or returned.
+  It is not called when a `Future` is started in the state. Use
+  `#[state_machine_future(on_enter(fallible))]` for a hook that returns
+  `Result<(), Error>` instead, where an error fails the state machine just like
+  an error from a polling method would.
+
+* `#[state_machine_future(on_exit)]`: Used on a variant of the state machine
+  description `enum` that has a polling trait method. Adds an
+  `on_exit_blah(state: &mut Blah)` method to the polling trait, which is called
+  when the generated `Future` is dropped or `reset` while it is in this state, for
+  example because it was cancelled, so that the state can clean up after itself.
+  It is not called when the state is left by a transition, or once the `Future`
+  has resolved. Since the generated `Future` then implements `Drop`, it cannot be
+  moved out of.
+
+* `#[state_machine_future(poll_fn = "handle_blah")]`: Used on a variant of the
+  state machine description `enum` that has a polling trait method. Names that
+  method `handle_blah` instead of the default `poll_blah`. Polling trait method
+  names must not collide with each other, or with the `on_transition` and
+  `validate_start` methods.
+
+* `#[state_machine_future(repoll(max = 3))]`: Used on a variant of the state
+  machine description `enum` that has a polling trait method. When that method
+  returns an error without taking the state out of its `RentToOwn`, the state
+  machine drops the error and polls the state again straight away, up to `max`
+  times in a row, before handling the last error as usual. Since futures 0.1 has
+  no timer, there is no delay between polls; a state that needs a backoff can
+  store a timer future and return `NotReady` until it fires.
+
+* `#[state_machine_future(entry)]`: Used on an intermediate variant of the state
+  machine description `enum`. Generates a `start_at_blah` function on the
+  description type, which takes the same arguments as `start` would for this
+  state, and returns a `Future` that begins in this state instead of the **start**
+  state. Any number of states may be entry states, but there is still exactly one
+  **start** state. Entry states count as reachable even when no other state
+  transitions to them.
+
+* `#[state_machine_future(stream)]`: Placed on the `enum` that describes the
+  state machine. Instead of a `Future`, generates a `BlahStream` type that
+  implements `Stream`. Each time the state machine enters a `yields` state, the
+  stream yields an item. The stream ends when the state machine reaches its
+  **ready** state, whose value is dropped.
+
+* `#[state_machine_future(yields)]`: Used on a variant of the state machine
+  description `enum` of a `stream` state machine. It must be a tuple-style variant
+  with two fields, for example `Token(MyItem, Scanning)`, and must transition to
+  exactly one state, the state held in its second field. Upon entering this
+  state, the stream yields the first field as its `Stream::Item` and moves into
+  the second field's state. There is no polling trait method for `yields` states.
+
+* `#[state_machine_future(error_type = "MyStateError")]`: Used on a variant of
+  the state machine description `enum` that has a polling trait method. That
+  method returns `MyStateError` errors instead of the state machine's error type,
+  and the generated `Future` converts them with `Into`. This lets different
+  states fail with different error types, as long as each converts into the
+  **error** state's type.
+
+* `#[state_machine_future(boxed)]`: Used on a variant of the state machine
+  description `enum`. Stores this state's typestate behind a `Box` inside the
+  generated `Future`, so that one large state does not make the whole `Future`
+  large. The polling trait method still receives a `RentToOwn` of the unboxed
+  typestate.
+  This also lets a state hold the state machine's own `Future`, which would
+  otherwise make the `Future` infinitely large.
+
+* `#[state_machine_future(future = "MyFuture")]`: Placed on the `enum` that
+  describes the state machine. Names the generated `Future` (or `Stream`) type
+  `MyFuture`, instead of `BlahFuture` (or `BlahStream`).
+
+* `#[state_machine_future(module = "blah_internals")]`: Placed on the `enum`
+  that describes the state machine. Puts all of the generated items (the
+  typestates, `After*` `enum`s, polling trait, `BlahState` `enum`, and `Future`)
+  in a `blah_internals` module with the same visibility as the description, and
+  re-exports them next to the description with a glob `use`. The description's
+  module can still declare items with the same names, such as its own `Start`
+  type; those shadow the generated items, which are then named through the
+  module, as in `blah_internals::Start`. A private description's generated items
+  are `pub(super)`, so that they can still be named from the description's
+  module.
+
+* `#[state_machine_future(future_attr(derive(Default)))]`: Placed on the `enum`
+  that describes the state machine. Puts each attribute in the list on the
+  generated `Future` (or `Stream`) type, and on nothing else. Attributes that
+  `derive(StateMachineFuture)` cannot parse itself, such as those with paths like
+  `allow(clippy::large_enum_variant)`, may be given as strings instead:
+  `future_attr("allow(clippy::large_enum_variant)")`.
+
+* `#[state_machine_future(assert_send)]` and
+  `#[state_machine_future(assert_sync)]`: Placed on the `enum` that describes the
+  state machine. Fails the build if the generated `Future` is not `Send` (or
+  `Sync`), pointing at the derive, rather than wherever the `Future` is eventually
+  sent to another thread. For generic state machines, the type parameters must be
+  bounded so that this holds for every instantiation, for example `T: Send`.
+
+* `#[state_machine_future(ready_bound = "Send + 'static")]`: Placed on the
+  `enum` that describes the state machine. Fails the build, pointing at the
+  derive, if the **ready** state's item does not meet the given bounds, so that a
+  state machine whose result gets handed to a spawned task cannot quietly start
+  resolving to something that can't be. As with `assert_send`, a generic item
+  needs bounds on its type parameters that make this hold.
+
+* `#[state_machine_future(stall_info)]`: Placed on the `enum` that describes the
+  state machine. Adds a `stall_info` method to the generated `Future`, which, in
+  debug builds, returns the `BlahState` that the last poll returned `NotReady` in,
+  and how many consecutive polls have returned `NotReady` in that state. This
+  helps track down state machines that are stuck waiting on something. Any
+  `derive` on the state machine also applies to this record, so it must be
+  implemented by the `BlahState` enum.
+
+* `#[state_machine_future(no_start_fn)]`: Placed on the `enum` that describes
+  the state machine. Skips generating the `start` function, so that you can write
+  your own constructor instead. The generated `Future` then implements
+  `From<Start>` for the **start** state's type, which is how your constructor
+  builds it:
+
+```ignore
+pub fn my_state_machine(x: usize) -> MyStateMachineFuture {
+    MyStateMachineFuture::from(Start(x))
+}
+```
+
+* `#[state_machine_future(no_start_loops)]`: Placed on the `enum` that describes
+  the state machine. Rejects any state that lists the **start** state in its
+  `transitions`, for state machines where looping back to the beginning would be a
+  mistake. Without it, such loops are allowed.
+
+* `#[state_machine_future(warn_ready_and_error)]`: Placed on the `enum` that
+  describes the state machine. Warns about every state that lists both the
+  **ready** and **error** states in its `transitions`, since such a state can
+  already fail by returning `Err` from its polling trait method. When the
+  transition to the **error** state is intended, add `#[allow(deprecated)]` to
+  that state's variant to silence its warning.
+
+* `#[state_machine_future(sync)]`: Placed on the `enum` that describes the
+  state machine. Adds a `step` method to the generated `Future`, for driving it
+  without an executor. Each call polls the current state once, following a
+  transition into the **ready** or **error** state straight away, and returns a
+  `StepResult`: `Done` with the result once the state machine has finished, and
+  `Pending` otherwise. A state that returns `NotReady` leaves the state machine
+  where it is, so `step` returns `Pending` and can simply be called again. The
+  generated type still implements `Future`. This cannot be combined with
+  `stream`.
+
+* `#[state_machine_future(inline_rent_to_own)]`: Placed on the `enum` that
+  describes the state machine. Generates a `BlahRentToOwn` type next to the state
+  machine, with the same `take` and deref behavior as `RentToOwn`, and uses it in
+  the polling trait's method signatures instead. The generated code then does not
+  use the `rent_to_own` crate at all, so if every state machine in a crate is
+  `inline_rent_to_own`, it can turn off this crate's default `rent_to_own` feature
+  (see [Features](#features)):
+
+```ignore
+fn poll_blah<'a>(blah: &'a mut MyStateMachineRentToOwn<'a, Blah>) -> Poll<AfterBlah, Error> {
+    // ...
+}
+```
+
+* `#[state_machine_future(record_path)]`: Placed on the `enum` that describes
+  the state machine. Adds a `path` method to the generated `Future`, which returns
+  every `BlahState` that the `Future` has entered so far, in order and including
+  repeats, starting with the state it was started in. Unlike `coverage`, this is
+  per `Future`, which makes it a good fit for snapshot tests of a state machine's
+  behavior.
+
+* `#[state_machine_future(transition_events)]`: Placed on the `enum` that
+  describes the state machine. Adds a `transition_events` method to the generated
+  `Future`, which turns it into a `BlahTransitionEvents` `Stream` of the
+  `BlahState`s that it enters, one for each transition, for feeding a live
+  dashboard. The stream ends when the state machine is ready, dropping its ready
+  value, or with the state machine's error. Not available for `stream` state
+  machines.
+
+* `#[state_machine_future(state_enum)]`: Placed on the `enum` that describes
+  the state machine. Generates a `BlahState` `enum`, which mirrors the description
+  `enum` with a fieldless variant for each state. This is named after the
+  description, so a state may not itself be named `BlahState`. It implements
+  `Clone`, `Copy`, `Debug`, `PartialEq`, and `Eq`, as well as `Display`, which
+  writes each state's name exactly as it is spelled in the description `enum`.
+  Every attribute that uses `BlahState` turns this on too.
+
+* `#[state_machine_future(current_state)]`: Placed on the `enum` that describes
+  the state machine. Adds a `current_state` method to the generated `Future`,
+  which returns the `BlahState` it is in, or `None` once it has resolved.
+
+* `#[state_machine_future(transitions_const)]`: Placed on the `enum` that
+  describes the state machine. Adds a `TRANSITIONS` constant to the description
+  type, which lists each state's name alongside the names of the states it may
+  transition to. This is handy for tooling and tests that want to inspect the
+  state machine's shape from Rust:
+
+```ignore
+assert_eq!(
+    MyStateMachine::TRANSITIONS[0],
+    ("Start", &["Intermediate"][..])
+);
+```
+
+* `#[state_machine_future(transitions_from)]`: Placed on the `enum` that
+  describes the state machine. Adds a `transitions_from` function to the
+  description type, the typed counterpart of `TRANSITIONS`, which takes a
+  `BlahState` and returns the `BlahState`s it may transition to.
+
+* `#[state_machine_future(all_states)]`: Placed on the `enum` that describes
+  the state machine. Adds an `all_states` function to the description type, which
+  returns an iterator over every `BlahState`, in declaration order, for
+  documentation generators and admin pages that want to list the full set of
+  states.
+
+* `#[state_machine_future(mermaid)]`: Placed on the `enum` that describes the
+  state machine. Adds a `MERMAID` constant to the description type, which holds a
+  [Mermaid](https://mermaid.js.org/) `stateDiagram-v2` of the state machine. The
+  **start** state is entered from `[*]`, and the **ready** and **error** states
+  exit to `[*]`.
+
+* `#[state_machine_future(reset)]`: Placed on the `enum` that describes the
+  state machine. Adds a `reset` method to the generated `Future`, with the same
+  parameters as `start`, which drops whatever state the `Future` is in, after
+  running its `on_exit` hook if it has one, and rewinds it to a fresh **start**
+  state. This lets a finished state machine run again.
+
+* `#[state_machine_future(is_finished)]`: Placed on the `enum` that describes
+  the state machine. Adds an `is_finished` method to the generated `Future`, which
+  cheaply checks whether it has reached its **ready** or **error** state, without
+  polling it.
+
+* `#[state_machine_future(map_machine_err)]`: Placed on the `enum` that
+  describes the state machine. Adds a `map_machine_err` method to the generated
+  `Future`, which converts the state machine's errors with a function, like
+  `Future::map_err`, but without having to name the trait.
+
+* `#[state_machine_future(poll_one)]`: Placed on the `enum` that describes the
+  state machine. Adds a `poll_one` method to the generated `Future`, which polls
+  the current state exactly once, rather than until it returns `NotReady`, so that
+  tests can step through the state machine one state at a time.
+
+* `#[state_machine_future(into_boxed)]`: Placed on the `enum` that describes
+  the state machine. Adds an `into_boxed` method to the generated `Future`, which
+  turns it into a `Box<dyn Future<Item = ..., Error = ...>>` (or a boxed
+  `Stream`), so that state machines of different shapes can be stored together.
+  The box is `Send` when the state machine has `assert_send`.
+
+* `#[state_machine_future(repr = "u8")]`: Placed on the `enum` that describes
+  the state machine. Gives the generated `BlahState` enum a `#[repr(u8)]` (or any
+  other integer type), with discriminants counting up from `0` in declaration
+  order, so that the current state can be passed across an FFI boundary or logged
+  as a small integer.
+
+* `#[state_machine_future(max_size = 256)]`: Placed on the `enum` that
+  describes the state machine. Fails to compile if the generated `Future` takes up
+  more than the given number of bytes, so that a state that grows by accident gets
+  noticed and boxed (see `boxed`) before it makes every caller's `Future` bigger.
+  For a state machine with type parameters, the size is only known once the type
+  arguments are, so the check is made for each set of type arguments the `Future`
+  is polled with; a `Future` that is created but never polled is not checked. The
+  check uses `assert!` in a constant, which needs Rust 1.57 or newer.
+
+* `#[state_machine_future(coverage)]`: Placed on the `enum` that describes the
+  state machine. Records every state that is entered, by starting a `Future` in it
+  or by a transition, across all of the state machine's `Future`s in the process.
+  Adds a `coverage` associated function to the description type that returns the
+  names of the entered states, in declaration order, and a `reset_coverage`
+  function that clears the record. This is intended for tests that want to check
+  that their inputs exercise every state. Since the record is shared, tests that
+  run in parallel should each use their own state machine.
+
+* `#[state_machine_future(poll_trait_bound = "Send")]`: Placed on the `enum`
+  that describes the state machine. Gives the generated `PollBlah` trait the given
+  supertraits (any `+`-separated bounds, such as `"Send + 'static"`), so that only
+  a type meeting them can implement the state machine's logic. This is handy when
+  the state machine's `Future` is going to be spawned on a threaded executor.
+
+* `#[state_machine_future(on_transition = "record")]`: Placed on the `enum` that
+  describes the state machine. Adds a `record` method to the polling trait, which
+  is called with the state being left and the state being entered after every
+  successful transition, including a failure that moves the state machine into
+  its error state. Both are values of the generated `BlahState` enum, which this
+  attribute turns on. The method does nothing by default, so only override it when
+  you want to observe transitions, for example to gather metrics:
+
+```ignore
+impl PollMyStateMachine for MyStateMachine {
+    // ...
+
+    fn record(from: MyStateMachineState, to: MyStateMachineState) {
+        println!("{:?} -> {:?}", from, to);
+    }
+}
+```
+
+Like the other polling trait methods, `record` takes no `self`: the trait is
+implemented by the description `enum`, and no value of it exists while the
+state machine runs. Keep any state that `record` needs in a `static` or
+`thread_local!`.
+
+* `#[state_machine_future(validate_start = "check")]`: Placed on the `enum` that
+  describes the state machine. Adds a required `check` method to the polling
+  trait, which takes a reference to the **start** state and returns a
+  `Result<(), Error>`. It also generates a `try_start` method next to `start`,
+  with the same parameters, which runs `check` on the start state and returns
+  either its error or the state machine `Future`.
+
+Any `#[allow(...)]` attributes on a state variant are copied onto the items
+generated for that state only: its typestate, its `After*` `enum`, and that
+`enum`'s impls. They do not affect the other states.
 
 ## Macro
 
@@ -448,10 +816,19 @@ Can be reduced to:
 
 ## Features
 
-Here are the `cargo` features that you can enable:
+Here are the `cargo` features:
 
 * `debug_code_generation`: Prints the code generated by
-`#[derive(StateMachineFuture)]` to `stdout` for debugging purposes.
+  `#[derive(StateMachineFuture)]` to `stdout` for debugging purposes.
+
+* `rent_to_own` (on by default): Depends on the [`rent_to_own`][rent_to_own]
+  crate, for the `RentToOwn` re-export that the polling trait methods take their
+  state through. Without it, every state machine must be `inline_rent_to_own`.
+
+## Minimum Rust version
+
+The code that `derive(StateMachineFuture)` generates uses `dyn Trait` and
+`impl Iterator`, and `max_size` needs Rust 1.57 or newer, as described above.
 
 ## License
 
@@ -484,6 +861,7 @@ dual licensed as above, without any additional terms or conditions.
 #![deny(missing_debug_implementations)]
 
 extern crate futures;
+#[cfg(feature = "rent_to_own")]
 extern crate rent_to_own;
 
 // Re-export the custom derive. This allows people to depend only on this crate
@@ -498,6 +876,7 @@ mod compile_fail_tests;
 mod transition;
 
 /// Re-export of `rent_to_own::RentToOwn`.
+#[cfg(feature = "rent_to_own")]
 pub type RentToOwn<'a, T> = rent_to_own::RentToOwn<'a, T>;
 
 /// A trait that links an `enum` with `#[derive(StateMachineFuture)]` to its
@@ -506,3 +885,21 @@ pub trait StateMachineFuture {
     /// The generated `Future` type for this state machine.
     type Future: futures::Future;
 }
+
+/// A trait that links an `enum` with `#[derive(StateMachineFuture)]` and
+/// `#[state_machine_future(stream)]` to its generated type that implements
+/// `Stream`.
+pub trait StateMachineStream {
+    /// The generated `Stream` type for this state machine.
+    type Stream: futures::Stream;
+}
+
+/// The result of advancing a `#[state_machine_future(sync)]` state machine by
+/// one step with its generated `step` method.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StepResult<T, E> {
+    /// The state machine has not finished yet; call `step` again.
+    Pending,
+    /// The state machine reached its **ready** state, or failed.
+    Done(Result<T, E>),
+}
@@ -0,0 +1,60 @@
+//! Test the generated `into_*` constructors on `After*` enums.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+pub enum Branch {
+    #[state_machine_future(start, transitions(Small, Medium, Large))]
+    Start(usize),
+
+    #[state_machine_future(transitions(Done))]
+    Small,
+
+    #[state_machine_future(transitions(Done))]
+    Medium(usize, bool),
+
+    #[state_machine_future(transitions(Done))]
+    Large { size: usize },
+
+    #[state_machine_future(ready)]
+    Done(&'static str),
+
+    #[state_machine_future(error)]
+    Error(()),
+}
+
+impl PollBranch for Branch {
+    fn poll_start<'a>(start: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, ()> {
+        Ok(Async::Ready(match start.0 {
+            0..=9 => AfterStart::into_small(),
+            10..=99 => AfterStart::into_medium(start.0, true),
+            size => AfterStart::into_large(size),
+        }))
+    }
+
+    fn poll_small<'a>(_: &'a mut RentToOwn<'a, Small>) -> Poll<AfterSmall, ()> {
+        Ok(Async::Ready(AfterSmall::into_done("small")))
+    }
+
+    fn poll_medium<'a>(medium: &'a mut RentToOwn<'a, Medium>) -> Poll<AfterMedium, ()> {
+        assert!(medium.1);
+        Ok(Async::Ready(AfterMedium::into_done("medium")))
+    }
+
+    fn poll_large<'a>(large: &'a mut RentToOwn<'a, Large>) -> Poll<AfterLarge, ()> {
+        assert!(large.size >= 100);
+        Ok(Async::Ready(AfterLarge::into_done("large")))
+    }
+}
+
+#[test]
+fn three_way_branch() {
+    assert_eq!(Branch::start(1).poll(), Ok(Async::Ready("small")));
+    assert_eq!(Branch::start(42).poll(), Ok(Async::Ready("medium")));
+    assert_eq!(Branch::start(1000).poll(), Ok(Async::Ready("large")));
+}
@@ -0,0 +1,81 @@
+//! Test enumerating every declared state with `all_states`.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::Poll;
+use state_machine_future::RentToOwn;
+use std::marker::PhantomData;
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(all_states)]
+pub enum Order {
+    #[state_machine_future(start, transitions(Paid, Cancelled))]
+    Placed(()),
+
+    #[state_machine_future(transitions(Shipped))]
+    Paid(()),
+
+    #[state_machine_future(ready)]
+    Shipped(()),
+
+    #[state_machine_future(error)]
+    Cancelled(()),
+}
+
+impl PollOrder for Order {
+    fn poll_placed<'a>(_: &'a mut RentToOwn<'a, Placed>) -> Poll<AfterPlaced, ()> {
+        transition!(Paid(()))
+    }
+
+    fn poll_paid<'a>(_: &'a mut RentToOwn<'a, Paid>) -> Poll<AfterPaid, ()> {
+        transition!(Shipped(()))
+    }
+}
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(all_states)]
+pub enum Generic<T: 'static> {
+    #[state_machine_future(start, transitions(Finished))]
+    Begin(PhantomData<T>),
+
+    #[state_machine_future(ready)]
+    Finished(()),
+
+    #[state_machine_future(error)]
+    Broken(()),
+}
+
+impl<T: 'static> PollGeneric<T> for Generic<T> {
+    fn poll_begin<'a>(_: &'a mut RentToOwn<'a, Begin<T>>) -> Poll<AfterBegin, ()> {
+        transition!(Finished(()))
+    }
+}
+
+#[test]
+fn all_states_are_in_declaration_order() {
+    let states: Vec<_> = Order::all_states().collect();
+    assert_eq!(
+        states,
+        vec![
+            OrderState::Placed,
+            OrderState::Paid,
+            OrderState::Shipped,
+            OrderState::Cancelled,
+        ]
+    );
+}
+
+#[test]
+fn generic_machines_list_their_states() {
+    let states: Vec<_> = Generic::<String>::all_states().collect();
+    assert_eq!(
+        states,
+        vec![
+            GenericState::Begin,
+            GenericState::Finished,
+            GenericState::Broken,
+        ]
+    );
+}
@@ -0,0 +1,57 @@
+//! Test that `assert_send` and `assert_sync` accept state machines whose
+//! futures are `Send` and `Sync`.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::Poll;
+use state_machine_future::RentToOwn;
+use std::sync::Arc;
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(assert_send, assert_sync)]
+pub enum Shared {
+    #[state_machine_future(start, transitions(Ready))]
+    Start(Arc<String>),
+
+    #[state_machine_future(ready)]
+    Ready(usize),
+
+    #[state_machine_future(error)]
+    Error(()),
+}
+
+impl PollShared for Shared {
+    fn poll_start<'a>(start: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, ()> {
+        transition!(Ready(start.0.len()))
+    }
+}
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(assert_send)]
+pub enum Generic<T: Send> {
+    #[state_machine_future(start, transitions(GenericReady))]
+    GenericStart(T),
+
+    #[state_machine_future(ready)]
+    GenericReady(T),
+
+    #[state_machine_future(error)]
+    GenericError(()),
+}
+
+impl<T: Send> PollGeneric<T> for Generic<T> {
+    fn poll_generic_start<'a>(
+        start: &'a mut RentToOwn<'a, GenericStart<T>>,
+    ) -> Poll<AfterGenericStart<T>, ()> {
+        let GenericStart(t) = start.take();
+        transition!(GenericReady(t))
+    }
+}
+
+#[test]
+fn send_and_sync_futures() {
+    fn check<T: Send + Sync>(_: T) {}
+    check(Shared::start(Arc::new("hello".into())));
+}
@@ -0,0 +1,95 @@
+//! Test that `boxed` states are stored behind a `Box` in the generated future.
+
+// The description `enum` is deliberately lopsided; that's the whole point.
+#![allow(clippy::large_enum_variant)]
+
+#[macro_use]
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+use std::mem;
+
+pub struct Big([u8; 4096]);
+
+#[derive(StateMachineFuture)]
+pub enum Machine {
+    #[state_machine_future(start, transitions(Loaded))]
+    Start,
+
+    #[state_machine_future(boxed, transitions(Ready))]
+    Loaded(Big, bool),
+
+    #[state_machine_future(ready)]
+    Ready(usize),
+
+    #[state_machine_future(error)]
+    Error(()),
+}
+
+impl PollMachine for Machine {
+    fn poll_start<'a>(_: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, ()> {
+        transition!(Loaded(Big([1; 4096]), false))
+    }
+
+    fn poll_loaded<'a>(loaded: &'a mut RentToOwn<'a, Loaded>) -> Poll<AfterLoaded, ()> {
+        // Go through the `NotReady` path once, to make sure the state gets put
+        // back into its box.
+        if !loaded.1 {
+            loaded.1 = true;
+            return Ok(Async::NotReady);
+        }
+
+        let Loaded(big, _) = loaded.take();
+        transition!(Ready(big.0.iter().map(|b| *b as usize).sum()))
+    }
+}
+
+#[test]
+fn boxed_state_does_not_bloat_future() {
+    assert!(mem::size_of::<MachineFuture>() < mem::size_of::<Big>());
+    assert!(mem::size_of::<MachineFuture>() <= 2 * mem::size_of::<usize>());
+}
+
+#[test]
+fn boxed_state_transitions() {
+    let mut machine = Machine::start();
+    assert_eq!(machine.poll(), Ok(Async::NotReady));
+    assert_eq!(machine.poll(), Ok(Async::Ready(4096)));
+}
+
+#[derive(StateMachineFuture)]
+pub enum Nest {
+    #[state_machine_future(start, transitions(Inner, Leaf))]
+    Depth(usize),
+
+    #[state_machine_future(boxed, transitions(Leaf))]
+    Inner(NestFuture),
+
+    #[state_machine_future(ready)]
+    Leaf(usize),
+
+    #[state_machine_future(error)]
+    NestError(()),
+}
+
+impl PollNest for Nest {
+    fn poll_depth<'a>(depth: &'a mut RentToOwn<'a, Depth>) -> Poll<AfterDepth, ()> {
+        match depth.0 {
+            0 => transition!(Leaf(0)),
+            n => transition!(Inner(Nest::start(n - 1))),
+        }
+    }
+
+    fn poll_inner<'a>(inner: &'a mut RentToOwn<'a, Inner>) -> Poll<AfterInner, ()> {
+        let leaf = try_ready!(inner.0.poll());
+        transition!(Leaf(leaf + 1))
+    }
+}
+
+#[test]
+fn boxed_state_can_hold_its_own_future() {
+    assert_eq!(Nest::start(3).poll(), Ok(Async::Ready(3)));
+}
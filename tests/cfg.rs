@@ -0,0 +1,87 @@
+//! Test that states behind `#[cfg(...)]` disappear entirely when they are
+//! configured out, and work as usual when they are configured in.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(transitions_const)]
+pub enum Without {
+    #[state_machine_future(start)]
+    #[cfg_attr(not(test), state_machine_future(transitions(ExtraOff)))]
+    #[cfg_attr(test, state_machine_future(transitions(ReadyOff)))]
+    StartOff,
+
+    #[cfg(not(test))]
+    #[state_machine_future(transitions(ReadyOff))]
+    ExtraOff,
+
+    #[state_machine_future(ready)]
+    ReadyOff(usize),
+
+    #[state_machine_future(error)]
+    ErrorOff(()),
+}
+
+impl PollWithout for Without {
+    fn poll_start_off<'a>(_: &'a mut RentToOwn<'a, StartOff>) -> Poll<AfterStartOff, ()> {
+        #[cfg(not(test))]
+        transition!(ExtraOff);
+        #[cfg(test)]
+        transition!(ReadyOff(1))
+    }
+
+    #[cfg(not(test))]
+    fn poll_extra_off<'a>(_: &'a mut RentToOwn<'a, ExtraOff>) -> Poll<AfterExtraOff, ()> {
+        transition!(ReadyOff(2))
+    }
+}
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(transitions_const)]
+pub enum With {
+    #[state_machine_future(start)]
+    #[cfg_attr(test, state_machine_future(transitions(ExtraOn)))]
+    #[cfg_attr(not(test), state_machine_future(transitions(ReadyOn)))]
+    StartOn,
+
+    #[cfg(test)]
+    #[state_machine_future(transitions(ReadyOn))]
+    ExtraOn,
+
+    #[state_machine_future(ready)]
+    ReadyOn(usize),
+
+    #[state_machine_future(error)]
+    ErrorOn(()),
+}
+
+impl PollWith for With {
+    fn poll_start_on<'a>(_: &'a mut RentToOwn<'a, StartOn>) -> Poll<AfterStartOn, ()> {
+        #[cfg(test)]
+        transition!(ExtraOn);
+        #[cfg(not(test))]
+        transition!(ReadyOn(1))
+    }
+
+    #[cfg(test)]
+    fn poll_extra_on<'a>(_: &'a mut RentToOwn<'a, ExtraOn>) -> Poll<AfterExtraOn, ()> {
+        transition!(ReadyOn(2))
+    }
+}
+
+#[test]
+fn configured_out_state_is_skipped() {
+    assert_eq!(Without::start().poll(), Ok(Async::Ready(1)));
+    assert_eq!(Without::TRANSITIONS.len(), 3);
+}
+
+#[test]
+fn configured_in_state_is_visited() {
+    assert_eq!(With::start().poll(), Ok(Async::Ready(2)));
+    assert_eq!(With::TRANSITIONS.len(), 4);
+}
@@ -0,0 +1,73 @@
+//! Test that `coverage` reports exactly the states that were entered, and that
+//! `reset_coverage` clears them.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(coverage)]
+pub enum Parse {
+    #[state_machine_future(start, transitions(Digits, Word, Invalid))]
+    Start(&'static str),
+
+    #[state_machine_future(transitions(Finished))]
+    Digits(&'static str),
+
+    #[state_machine_future(transitions(Finished))]
+    Word(&'static str),
+
+    #[state_machine_future(ready)]
+    Finished(usize),
+
+    #[state_machine_future(error)]
+    Invalid(()),
+}
+
+impl PollParse for Parse {
+    fn poll_start<'a>(start: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, ()> {
+        let input = start.0;
+        if input.is_empty() {
+            transition!(Invalid(()))
+        }
+        if input.chars().all(|c| c.is_ascii_digit()) {
+            transition!(Digits(input))
+        }
+        transition!(Word(input))
+    }
+
+    fn poll_digits<'a>(digits: &'a mut RentToOwn<'a, Digits>) -> Poll<AfterDigits, ()> {
+        transition!(Finished(digits.0.parse().unwrap()))
+    }
+
+    fn poll_word<'a>(word: &'a mut RentToOwn<'a, Word>) -> Poll<AfterWord, ()> {
+        transition!(Finished(word.0.len()))
+    }
+}
+
+#[test]
+fn coverage_reports_entered_states() {
+    assert!(Parse::coverage().is_empty());
+
+    // Starting a `Future` enters its start state, even before it is polled.
+    let mut parse = Parse::start("42");
+    assert_eq!(Parse::coverage(), vec!["Start"]);
+
+    assert_eq!(parse.poll(), Ok(Async::Ready(42)));
+    assert_eq!(Parse::coverage(), vec!["Start", "Digits", "Finished"]);
+
+    assert_eq!(Parse::start("hello").poll(), Ok(Async::Ready(5)));
+    assert_eq!(
+        Parse::coverage(),
+        vec!["Start", "Digits", "Word", "Finished"]
+    );
+
+    Parse::reset_coverage();
+    assert!(Parse::coverage().is_empty());
+
+    assert_eq!(Parse::start("").poll(), Err(()));
+    assert_eq!(Parse::coverage(), vec!["Start", "Invalid"]);
+}
@@ -0,0 +1,51 @@
+//! Test that `current_state` reports the state a future is in, comparably.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(current_state)]
+pub enum Gate {
+    #[state_machine_future(start, transitions(Open))]
+    Closed(bool),
+
+    #[state_machine_future(transitions(Passed))]
+    Open(()),
+
+    #[state_machine_future(ready)]
+    Passed(()),
+
+    #[state_machine_future(error)]
+    Jammed(()),
+}
+
+impl PollGate for Gate {
+    fn poll_closed<'a>(closed: &'a mut RentToOwn<'a, Closed>) -> Poll<AfterClosed, ()> {
+        if closed.0 {
+            transition!(Open(()))
+        }
+        closed.0 = true;
+        Ok(Async::NotReady)
+    }
+
+    fn poll_open<'a>(_: &'a mut RentToOwn<'a, Open>) -> Poll<AfterOpen, ()> {
+        transition!(Passed(()))
+    }
+}
+
+#[test]
+fn current_state_is_comparable() {
+    let mut gate = Gate::start(false);
+    assert_eq!(gate.current_state(), Some(GateState::Closed));
+
+    assert_eq!(gate.poll(), Ok(Async::NotReady));
+    assert_eq!(gate.current_state(), Some(GateState::Closed));
+
+    assert_eq!(gate.poll(), Ok(Async::Ready(())));
+    assert_eq!(gate.current_state(), None);
+    assert_ne!(GateState::Open, GateState::Passed);
+}
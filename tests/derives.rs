@@ -11,7 +11,6 @@ use std::fmt::Debug;
 pub enum Debuggable {
     #[state_machine_future(start)]
     #[state_machine_future(ready)]
-    #[state_machine_future(error)]
     OnlyState(()),
 }
 
@@ -26,3 +25,80 @@ fn state_derived_debug() {
 fn state_machine_derived_debug() {
     check_debug(Debuggable::start(()));
 }
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(derive(Debug, PartialEq))]
+pub enum Comparable {
+    #[state_machine_future(start, transitions(Finished, Failed))]
+    Begin(usize),
+
+    #[state_machine_future(ready)]
+    Finished(usize),
+
+    #[state_machine_future(error)]
+    Failed(()),
+}
+
+impl PollComparable for Comparable {
+    fn poll_begin<'a>(
+        _: &'a mut state_machine_future::RentToOwn<'a, Begin>,
+    ) -> futures::Poll<AfterBegin, ()> {
+        unimplemented!()
+    }
+}
+
+#[test]
+fn after_enum_derived_partial_eq() {
+    let finished: AfterBegin = Finished(1).into();
+    assert_eq!(finished, AfterBegin::Finished(Finished(1)));
+    assert_ne!(finished, Failed(()).into());
+    check_debug(finished);
+}
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(derive(Debug, PartialEq, Eq, PartialOrd, Ord))]
+pub enum Ordered {
+    #[state_machine_future(start, transitions(Zebra, Apple, Mango))]
+    Pick(()),
+
+    #[state_machine_future(transitions(Mango))]
+    Zebra(()),
+
+    #[state_machine_future(transitions(Mango))]
+    Apple(()),
+
+    #[state_machine_future(ready)]
+    Mango(()),
+
+    #[state_machine_future(error)]
+    Rotten(()),
+}
+
+impl PollOrdered for Ordered {
+    fn poll_pick<'a>(
+        _: &'a mut state_machine_future::RentToOwn<'a, Pick>,
+    ) -> futures::Poll<AfterPick, ()> {
+        unimplemented!()
+    }
+
+    fn poll_zebra<'a>(
+        _: &'a mut state_machine_future::RentToOwn<'a, Zebra>,
+    ) -> futures::Poll<AfterZebra, ()> {
+        unimplemented!()
+    }
+
+    fn poll_apple<'a>(
+        _: &'a mut state_machine_future::RentToOwn<'a, Apple>,
+    ) -> futures::Poll<AfterApple, ()> {
+        unimplemented!()
+    }
+}
+
+#[test]
+fn after_enum_variants_follow_declared_transitions() {
+    let zebra: AfterPick = Zebra(()).into();
+    let apple: AfterPick = Apple(()).into();
+    let mango: AfterPick = Mango(()).into();
+    assert!(zebra < apple);
+    assert!(apple < mango);
+}
@@ -4,7 +4,7 @@ extern crate futures;
 #[macro_use]
 extern crate state_machine_future;
 
-use futures::Poll;
+use futures::{Async, Future, Poll};
 use state_machine_future::RentToOwn;
 
 #[derive(StateMachineFuture)]
@@ -20,8 +20,10 @@ pub enum Fsm {
     Struct { x: usize, y: bool },
 
     #[state_machine_future(ready)]
-    #[state_machine_future(error)]
     Done(()),
+
+    #[state_machine_future(error)]
+    Error(()),
 }
 
 impl PollFsm for Fsm {
@@ -41,3 +43,60 @@ impl PollFsm for Fsm {
         }
     }
 }
+
+#[derive(StateMachineFuture)]
+pub enum Shapes {
+    #[state_machine_future(start, transitions(Working))]
+    Idle,
+
+    #[state_machine_future(transitions(Working, Sending))]
+    Working { retries: u32, total: u32 },
+
+    #[state_machine_future(transitions(Sent))]
+    Sending(u32, &'static str),
+
+    #[state_machine_future(ready)]
+    Sent(String),
+
+    #[state_machine_future(error)]
+    Failed(()),
+}
+
+impl PollShapes for Shapes {
+    fn poll_idle<'a>(_: &'a mut RentToOwn<'a, Idle>) -> Poll<AfterIdle, ()> {
+        transition!(Working {
+            retries: 0,
+            total: 1,
+        })
+    }
+
+    fn poll_working<'a>(working: &'a mut RentToOwn<'a, Working>) -> Poll<AfterWorking, ()> {
+        if working.retries < 2 {
+            working.retries += 1;
+            working.total *= 10;
+            return Ok(Async::NotReady);
+        }
+
+        let Working { retries, total } = working.take();
+        if total < 1000 {
+            transition!(Working {
+                retries,
+                total: total * 10,
+            })
+        }
+        transition!(Sending(total, "total"))
+    }
+
+    fn poll_sending<'a>(sending: &'a mut RentToOwn<'a, Sending>) -> Poll<AfterSending, ()> {
+        let Sending(total, label) = sending.take();
+        transition!(Sent(format!("{} = {}", label, total)))
+    }
+}
+
+#[test]
+fn all_field_shapes_end_to_end() {
+    let mut machine = Shapes::start();
+    assert_eq!(machine.poll(), Ok(Async::NotReady));
+    assert_eq!(machine.poll(), Ok(Async::NotReady));
+    assert_eq!(machine.poll(), Ok(Async::Ready("total = 1000".to_string())));
+}
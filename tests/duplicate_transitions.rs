@@ -0,0 +1,43 @@
+//! Test that a state listed twice in `transitions` is only counted once.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::Poll;
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(transitions_const)]
+pub enum Machine {
+    #[state_machine_future(start, transitions(Bar, Done, Bar))]
+    #[allow(deprecated)]
+    Foo,
+
+    #[state_machine_future(transitions(Done))]
+    Bar,
+
+    #[state_machine_future(ready)]
+    Done(()),
+
+    #[state_machine_future(error)]
+    Failed(()),
+}
+
+impl PollMachine for Machine {
+    fn poll_foo<'a>(_: &'a mut RentToOwn<'a, Foo>) -> Poll<AfterFoo, ()> {
+        transition!(Bar)
+    }
+
+    fn poll_bar<'a>(_: &'a mut RentToOwn<'a, Bar>) -> Poll<AfterBar, ()> {
+        transition!(Done(()))
+    }
+}
+
+#[test]
+fn duplicates_are_collapsed() {
+    match AfterFoo::from(Bar) {
+        AfterFoo::Bar(Bar) | AfterFoo::Done(_) => {}
+    }
+    assert_eq!(Machine::TRANSITIONS[0], ("Foo", &["Bar", "Done"][..]));
+}
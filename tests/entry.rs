@@ -0,0 +1,53 @@
+//! Test starting a state machine in an `entry` state instead of its start
+//! state.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+pub enum Handshake {
+    #[state_machine_future(start, transitions(Negotiate))]
+    Connect(u32),
+
+    #[state_machine_future(entry, transitions(Established))]
+    Negotiate(u32),
+
+    #[state_machine_future(entry, boxed, transitions(Established))]
+    Resume { session: u32 },
+
+    #[state_machine_future(ready)]
+    Established(u32),
+
+    #[state_machine_future(error)]
+    Failed(()),
+}
+
+impl PollHandshake for Handshake {
+    fn poll_connect<'a>(connect: &'a mut RentToOwn<'a, Connect>) -> Poll<AfterConnect, ()> {
+        transition!(Negotiate(connect.0 + 1))
+    }
+
+    fn poll_negotiate<'a>(
+        negotiate: &'a mut RentToOwn<'a, Negotiate>,
+    ) -> Poll<AfterNegotiate, ()> {
+        transition!(Established(negotiate.0 * 10))
+    }
+
+    fn poll_resume<'a>(resume: &'a mut RentToOwn<'a, Resume>) -> Poll<AfterResume, ()> {
+        transition!(Established(resume.session))
+    }
+}
+
+#[test]
+fn entry_states_converge_to_ready() {
+    assert_eq!(Handshake::start(1).poll(), Ok(Async::Ready(20)));
+    assert_eq!(Handshake::start_at_negotiate(1).poll(), Ok(Async::Ready(10)));
+    assert_eq!(
+        Handshake::start_at_resume(7).poll(),
+        Ok(Async::Ready(7))
+    );
+}
@@ -0,0 +1,38 @@
+//! Test that the future's `Error` is the error state's inner type, not the
+//! error state itself.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Future, Poll};
+use state_machine_future::RentToOwn;
+
+#[derive(Debug, PartialEq)]
+pub struct MyError {
+    code: u32,
+}
+
+#[derive(StateMachineFuture)]
+pub enum Fallible {
+    #[state_machine_future(start, transitions(Done))]
+    Begin(u32),
+
+    #[state_machine_future(ready)]
+    Done(()),
+
+    #[state_machine_future(error)]
+    Failed(MyError),
+}
+
+impl PollFallible for Fallible {
+    fn poll_begin<'a>(begin: &'a mut RentToOwn<'a, Begin>) -> Poll<AfterBegin, MyError> {
+        Err(MyError { code: begin.0 })
+    }
+}
+
+#[test]
+fn poll_yields_inner_error() {
+    let result: Result<_, MyError> = Fallible::start(7).poll();
+    assert_eq!(result, Err(MyError { code: 7 }));
+}
@@ -0,0 +1,63 @@
+//! Test that states with their own `error_type` have their errors converted
+//! into the state machine's error type.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+use std::num::ParseIntError;
+use std::str::Utf8Error;
+
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    Utf8,
+    Int,
+}
+
+impl From<Utf8Error> for ParseError {
+    fn from(_: Utf8Error) -> Self {
+        ParseError::Utf8
+    }
+}
+
+impl From<ParseIntError> for ParseError {
+    fn from(_: ParseIntError) -> Self {
+        ParseError::Int
+    }
+}
+
+#[derive(StateMachineFuture)]
+pub enum Parse {
+    #[state_machine_future(start, transitions(Text))]
+    #[state_machine_future(error_type = "::std::str::Utf8Error")]
+    Bytes(&'static [u8]),
+
+    #[state_machine_future(transitions(Parsed))]
+    #[state_machine_future(error_type = "::std::num::ParseIntError")]
+    Text(&'static str),
+
+    #[state_machine_future(ready)]
+    Parsed(u32),
+
+    #[state_machine_future(error)]
+    Failed(ParseError),
+}
+
+impl PollParse for Parse {
+    fn poll_bytes<'a>(bytes: &'a mut RentToOwn<'a, Bytes>) -> Poll<AfterBytes, Utf8Error> {
+        transition!(Text(::std::str::from_utf8(bytes.0)?))
+    }
+
+    fn poll_text<'a>(text: &'a mut RentToOwn<'a, Text>) -> Poll<AfterText, ParseIntError> {
+        transition!(Parsed(text.0.parse()?))
+    }
+}
+
+#[test]
+fn errors_from_each_state_are_converted() {
+    assert_eq!(Parse::start(b"42").poll(), Ok(Async::Ready(42)));
+    assert_eq!(Parse::start(b"\xff").poll(), Err(ParseError::Utf8));
+    assert_eq!(Parse::start(b"forty-two").poll(), Err(ParseError::Int));
+}
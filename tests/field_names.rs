@@ -0,0 +1,61 @@
+//! Test that struct-variant states may have fields named like the generated
+//! functions and their locals.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(validate_start = "check", reset)]
+pub enum Named {
+    #[state_machine_future(start, boxed, transitions(Middle))]
+    Begin { start: u8, state: u8 },
+
+    #[state_machine_future(transitions(Done))]
+    Middle { next: u8, e: u8, result: u8 },
+
+    #[state_machine_future(ready)]
+    Done(u8),
+
+    #[state_machine_future(error)]
+    Failed(()),
+}
+
+impl PollNamed for Named {
+    fn poll_begin<'a>(begin: &'a mut RentToOwn<'a, Begin>) -> Poll<AfterBegin, ()> {
+        transition!(Middle {
+            next: begin.start,
+            e: begin.state,
+            result: 0,
+        })
+    }
+
+    fn poll_middle<'a>(middle: &'a mut RentToOwn<'a, Middle>) -> Poll<AfterMiddle, ()> {
+        transition!(Done(middle.next + middle.e + middle.result))
+    }
+
+    fn check(begin: &Begin) -> Result<(), ()> {
+        if begin.start > 100 {
+            return Err(());
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn helper_like_field_names() {
+    assert_eq!(Named::start(1, 2).poll(), Ok(Async::Ready(3)));
+    assert_eq!(Named::try_start(3, 4).unwrap().poll(), Ok(Async::Ready(7)));
+    assert!(Named::try_start(101, 0).is_err());
+
+    let mut named = Named::start(0, 0);
+    named.reset(5, 5);
+    assert_eq!(named.poll(), Ok(Async::Ready(10)));
+
+    match AfterBegin::into_middle(1, 2, 3) {
+        AfterBegin::Middle(Middle { next, e, result }) => assert_eq!((next, e, result), (1, 2, 3)),
+    }
+}
@@ -0,0 +1,83 @@
+//! Test that `finish` behaves exactly like listing the ready state in
+//! `transitions`.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(transitions_const)]
+pub enum Shorthand {
+    #[state_machine_future(start, finish, transitions(Retry))]
+    Start(usize),
+
+    #[state_machine_future(finish)]
+    Retry(usize),
+
+    #[state_machine_future(ready)]
+    Done(usize),
+
+    #[state_machine_future(error)]
+    Error(()),
+}
+
+impl PollShorthand for Shorthand {
+    fn poll_start<'a>(start: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, ()> {
+        if start.0 == 0 {
+            transition!(Retry(1))
+        }
+        transition!(Done(start.0))
+    }
+
+    fn poll_retry<'a>(retry: &'a mut RentToOwn<'a, Retry>) -> Poll<AfterRetry, ()> {
+        transition!(Done(retry.0 * 10))
+    }
+}
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(transitions_const)]
+pub enum Longhand {
+    #[state_machine_future(start, transitions(RetryLong, DoneLong))]
+    StartLong(usize),
+
+    #[state_machine_future(transitions(DoneLong))]
+    RetryLong(usize),
+
+    #[state_machine_future(ready)]
+    DoneLong(usize),
+
+    #[state_machine_future(error)]
+    ErrorLong(()),
+}
+
+impl PollLonghand for Longhand {
+    fn poll_start_long<'a>(start: &'a mut RentToOwn<'a, StartLong>) -> Poll<AfterStartLong, ()> {
+        if start.0 == 0 {
+            transition!(RetryLong(1))
+        }
+        transition!(DoneLong(start.0))
+    }
+
+    fn poll_retry_long<'a>(retry: &'a mut RentToOwn<'a, RetryLong>) -> Poll<AfterRetryLong, ()> {
+        transition!(DoneLong(retry.0 * 10))
+    }
+}
+
+#[test]
+fn finish_matches_explicit_ready_transition() {
+    for &n in &[0, 7] {
+        assert_eq!(
+            Shorthand::start(n).poll(),
+            Longhand::start(n).poll()
+        );
+    }
+    assert_eq!(Shorthand::start(0).poll(), Ok(Async::Ready(10)));
+
+    let shape = |transitions: &[(&str, &[&str])]| {
+        transitions.iter().map(|&(_, to)| to.len()).collect::<Vec<_>>()
+    };
+    assert_eq!(shape(Shorthand::TRANSITIONS), shape(Longhand::TRANSITIONS));
+}
@@ -0,0 +1,60 @@
+//! Test that `future_attr` forwards attributes to the generated future type.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(is_finished)]
+#[state_machine_future(future_attr(
+    derive(Default),
+    "allow(clippy::large_enum_variant)"
+))]
+pub enum Parsed {
+    #[state_machine_future(start, transitions(ParsedDone))]
+    ParsedStart(()),
+
+    #[state_machine_future(ready)]
+    ParsedDone(()),
+
+    #[state_machine_future(error)]
+    ParsedError(()),
+}
+
+impl PollParsed for Parsed {
+    fn poll_parsed_start<'a>(_: &'a mut RentToOwn<'a, ParsedStart>) -> Poll<AfterParsedStart, ()> {
+        transition!(ParsedDone(()))
+    }
+}
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(future_attr("cfg_attr(test, derive(Default))"), is_finished)]
+pub enum Raw {
+    #[state_machine_future(start, transitions(RawDone))]
+    RawStart(()),
+
+    #[state_machine_future(ready)]
+    RawDone(()),
+
+    #[state_machine_future(error)]
+    RawError(()),
+}
+
+impl PollRaw for Raw {
+    fn poll_raw_start<'a>(_: &'a mut RentToOwn<'a, RawStart>) -> Poll<AfterRawStart, ()> {
+        transition!(RawDone(()))
+    }
+}
+
+#[test]
+fn forwarded_attributes() {
+    // The derived `Default` is a future that has already resolved.
+    assert!(ParsedFuture::default().is_finished());
+    assert!(RawFuture::default().is_finished());
+
+    assert_eq!(Parsed::start(()).poll(), Ok(Async::Ready(())));
+    assert_eq!(Raw::start(()).poll(), Ok(Async::Ready(())));
+}
@@ -0,0 +1,36 @@
+//! Test that `future = "..."` renames the generated `Future` type.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(future = "Greeting")]
+pub enum Greeter {
+    #[state_machine_future(start, transitions(Greeted))]
+    Start(&'static str),
+
+    #[state_machine_future(ready)]
+    Greeted(String),
+
+    #[state_machine_future(error)]
+    Error(()),
+}
+
+impl PollGreeter for Greeter {
+    fn poll_start<'a>(start: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, ()> {
+        transition!(Greeted(format!("Hello, {}!", start.0)))
+    }
+}
+
+fn greet(name: &'static str) -> Greeting {
+    Greeter::start(name)
+}
+
+#[test]
+fn renamed_future() {
+    assert_eq!(greet("world").poll(), Ok(Async::Ready("Hello, world!".to_string())));
+}
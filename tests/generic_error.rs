@@ -0,0 +1,62 @@
+//! Test that a state machine may be generic over its error type.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+use std::io;
+use std::num::ParseIntError;
+
+pub trait Parse: Sized {
+    fn parse(input: &str) -> Result<u32, Self>;
+}
+
+impl Parse for ParseIntError {
+    fn parse(input: &str) -> Result<u32, Self> {
+        input.parse()
+    }
+}
+
+impl Parse for io::Error {
+    fn parse(input: &str) -> Result<u32, Self> {
+        input
+            .parse()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, input.to_string()))
+    }
+}
+
+#[derive(StateMachineFuture)]
+pub enum Parser<E: Parse> {
+    #[state_machine_future(start, transitions(Parsed))]
+    Unparsed(&'static str),
+
+    #[state_machine_future(ready)]
+    Parsed(u32),
+
+    #[state_machine_future(error)]
+    Invalid(E),
+}
+
+impl<E: Parse> PollParser<E> for Parser<E> {
+    fn poll_unparsed<'a>(unparsed: &'a mut RentToOwn<'a, Unparsed>) -> Poll<AfterUnparsed, E> {
+        let parsed = E::parse(unparsed.0)?;
+        transition!(Parsed(parsed))
+    }
+}
+
+#[test]
+fn error_type_follows_the_parameter() {
+    let mut ok = Parser::<ParseIntError>::start("42");
+    assert_eq!(ok.poll(), Ok(Async::Ready(42)));
+
+    let mut parse_error = Parser::<ParseIntError>::start("x");
+    let error: ParseIntError = parse_error.poll().unwrap_err();
+    assert_eq!(error, "x".parse::<u32>().unwrap_err());
+
+    let mut io_error = Parser::<io::Error>::start("y");
+    let error: io::Error = io_error.poll().unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(error.to_string(), "y");
+}
@@ -0,0 +1,57 @@
+//! Test a state machine that uses its own generated `RentToOwn` equivalent,
+//! without naming `state_machine_future::RentToOwn` anywhere.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+
+pub struct Payload {
+    name: String,
+    parts: Vec<u32>,
+}
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(inline_rent_to_own)]
+pub enum Assembly {
+    #[state_machine_future(start, transitions(Assembled))]
+    Collecting(Payload),
+
+    #[state_machine_future(ready)]
+    Assembled((String, u32)),
+
+    #[state_machine_future(error)]
+    Failed(()),
+}
+
+impl PollAssembly for Assembly {
+    fn poll_collecting<'a>(
+        collecting: &'a mut AssemblyRentToOwn<'a, Collecting>,
+    ) -> Poll<AfterCollecting, ()> {
+        if (collecting.0).parts.len() < 3 {
+            let next = (collecting.0).parts.len() as u32 + 1;
+            (collecting.0).parts.push(next);
+            return Ok(Async::NotReady);
+        }
+
+        let Collecting(Payload { name, parts }) = collecting.take();
+        transition!(Assembled((name, parts.into_iter().sum())))
+    }
+}
+
+#[test]
+fn inline_rent_to_own_takes_ownership() {
+    let mut assembly = Assembly::start(Payload {
+        name: "widget".to_string(),
+        parts: vec![],
+    });
+
+    assert_eq!(assembly.poll(), Ok(Async::NotReady));
+    assert_eq!(assembly.poll(), Ok(Async::NotReady));
+    assert_eq!(assembly.poll(), Ok(Async::NotReady));
+    assert_eq!(
+        assembly.poll(),
+        Ok(Async::Ready(("widget".to_string(), 6)))
+    );
+}
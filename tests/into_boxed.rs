@@ -0,0 +1,76 @@
+//! Test that `into_boxed` lets differently-shaped state machines with the same
+//! output be stored together.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+use std::thread;
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(assert_send, into_boxed)]
+pub enum Direct {
+    #[state_machine_future(start, transitions(DirectDone))]
+    DirectStart(u32),
+
+    #[state_machine_future(ready)]
+    DirectDone(u32),
+
+    #[state_machine_future(error)]
+    DirectError(()),
+}
+
+impl PollDirect for Direct {
+    fn poll_direct_start<'a>(
+        start: &'a mut RentToOwn<'a, DirectStart>,
+    ) -> Poll<AfterDirectStart, ()> {
+        transition!(DirectDone(start.0))
+    }
+}
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(assert_send, into_boxed)]
+pub enum Doubled {
+    #[state_machine_future(start, transitions(Doubling))]
+    DoubledStart(u32),
+
+    #[state_machine_future(transitions(DoubledDone))]
+    Doubling(u32, u32),
+
+    #[state_machine_future(ready)]
+    DoubledDone(u32),
+
+    #[state_machine_future(error)]
+    DoubledError(()),
+}
+
+impl PollDoubled for Doubled {
+    fn poll_doubled_start<'a>(
+        start: &'a mut RentToOwn<'a, DoubledStart>,
+    ) -> Poll<AfterDoubledStart, ()> {
+        transition!(Doubling(start.0, start.0))
+    }
+
+    fn poll_doubling<'a>(doubling: &'a mut RentToOwn<'a, Doubling>) -> Poll<AfterDoubling, ()> {
+        transition!(DoubledDone(doubling.0 + doubling.1))
+    }
+}
+
+#[test]
+fn heterogeneous_machines() {
+    let machines: Vec<Box<dyn Future<Item = u32, Error = ()> + Send>> = vec![
+        Direct::start(1).into_boxed(),
+        Doubled::start(2).into_boxed(),
+    ];
+
+    let results = thread::spawn(move || {
+        machines
+            .into_iter()
+            .map(|mut m| m.poll())
+            .collect::<Vec<_>>()
+    }).join()
+        .unwrap();
+    assert_eq!(results, vec![Ok(Async::Ready(1)), Ok(Async::Ready(4))]);
+}
@@ -0,0 +1,44 @@
+//! Test that a bound needed only by a state that is transitioned to, and not
+//! by the state transitioning to it, still makes it onto the `After` enum.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+pub struct Cloned<T: Clone>(T);
+
+#[derive(StateMachineFuture)]
+pub enum Late<T: Clone + Into<U>, U>
+where
+    U: Default,
+{
+    #[state_machine_future(start, transitions(Holding))]
+    Begin(T),
+
+    #[state_machine_future(transitions(Converted))]
+    Holding(Cloned<T>),
+
+    #[state_machine_future(ready)]
+    Converted(U),
+
+    #[state_machine_future(error)]
+    Failed(()),
+}
+
+impl<T: Clone + Into<U>, U: Default> PollLate<T, U> for Late<T, U> {
+    fn poll_begin<'a>(begin: &'a mut RentToOwn<'a, Begin<T>>) -> Poll<AfterBegin<T>, ()> {
+        transition!(Holding(Cloned(begin.0.clone())))
+    }
+
+    fn poll_holding<'a>(holding: &'a mut RentToOwn<'a, Holding<T>>) -> Poll<AfterHolding<U>, ()> {
+        transition!(Converted((holding.0).0.clone().into()))
+    }
+}
+
+#[test]
+fn late_state_bounds() {
+    assert_eq!(Late::<u8, u32>::start(7).poll(), Ok(Async::Ready(7)));
+}
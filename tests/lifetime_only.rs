@@ -0,0 +1,52 @@
+//! Test a state machine whose only generic parameter is a lifetime, with states
+//! that share a mutable borrow.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+pub struct Buf {
+    bytes: Vec<u8>,
+}
+
+#[derive(StateMachineFuture)]
+pub enum Fill<'a> {
+    #[state_machine_future(start, transitions(Filling))]
+    Begin(&'a mut Buf),
+
+    #[state_machine_future(transitions(Filling, Full))]
+    Filling(&'a mut Buf, u8),
+
+    #[state_machine_future(ready)]
+    Full(usize),
+
+    #[state_machine_future(error)]
+    Failed(()),
+}
+
+impl<'a> PollFill<'a> for Fill<'a> {
+    fn poll_begin<'b>(begin: &'b mut RentToOwn<'b, Begin<'a>>) -> Poll<AfterBegin<'a>, ()> {
+        let Begin(buf) = begin.take();
+        buf.bytes.clear();
+        transition!(Filling(buf, 0))
+    }
+
+    fn poll_filling<'b>(filling: &'b mut RentToOwn<'b, Filling<'a>>) -> Poll<AfterFilling<'a>, ()> {
+        let Filling(buf, next) = filling.take();
+        if next == 3 {
+            transition!(Full(buf.bytes.len()))
+        }
+        buf.bytes.push(next);
+        transition!(Filling(buf, next + 1))
+    }
+}
+
+#[test]
+fn lifetime_only_machine() {
+    let mut buf = Buf { bytes: vec![9] };
+    assert_eq!(Fill::start(&mut buf).poll(), Ok(Async::Ready(3)));
+    assert_eq!(buf.bytes, vec![0, 1, 2]);
+}
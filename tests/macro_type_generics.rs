@@ -0,0 +1,74 @@
+//! Test that generic parameters used as types inside a type macro are kept, and
+//! that ones only mentioned as values are not.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+macro_rules! pair {
+    ($t:ty) => {
+        ($t, $t)
+    };
+}
+
+#[derive(StateMachineFuture)]
+pub enum Swap<T> {
+    #[state_machine_future(start, transitions(Swapped))]
+    Swapping(pair!(T)),
+
+    #[state_machine_future(ready)]
+    Swapped(pair!(T)),
+
+    #[state_machine_future(error)]
+    Failed(()),
+}
+
+impl<T> PollSwap<T> for Swap<T> {
+    fn poll_swapping<'a>(swapping: &'a mut RentToOwn<'a, Swapping<T>>) -> Poll<AfterSwapping<T>, ()> {
+        let Swapping((a, b)) = swapping.take();
+        transition!(Swapped((b, a)))
+    }
+}
+
+#[test]
+fn type_macro_generics() {
+    assert_eq!(Swap::start((1, 2)).poll(), Ok(Async::Ready((2, 1))));
+}
+
+macro_rules! labeled {
+    ($t:ty, label = $label:ident) => {
+        ($t, &'static str)
+    };
+}
+
+// `T` is only the label's value in `Start`'s macro, so `Start` is just
+// `Start<U>`, without the `T` that `U: Into<T>` would otherwise pull in.
+#[derive(StateMachineFuture)]
+pub enum Labeled<T, U: Into<T>> {
+    #[state_machine_future(start, transitions(Done))]
+    Start(labeled!(U, label = T)),
+
+    #[state_machine_future(ready)]
+    Done(T),
+
+    #[state_machine_future(error)]
+    Error(()),
+}
+
+impl<T, U: Into<T>> PollLabeled<T, U> for Labeled<T, U> {
+    fn poll_start<'a>(start: &'a mut RentToOwn<'a, Start<U>>) -> Poll<AfterStart<T>, ()> {
+        let Start((u, _)) = start.take();
+        transition!(Done(u.into()))
+    }
+}
+
+#[test]
+fn value_mentions_in_type_macros_are_not_uses() {
+    assert_eq!(
+        Labeled::<u64, u32>::start((7, "seven")).poll(),
+        Ok(Async::Ready(7u64))
+    );
+}
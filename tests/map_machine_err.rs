@@ -0,0 +1,49 @@
+//! Test that `map_machine_err` converts the state machine's error, and keeps
+//! its item.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::future::MapErr;
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+#[derive(Debug, PartialEq)]
+pub enum AppError {
+    Parse(String),
+}
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(map_machine_err)]
+pub enum Parse {
+    #[state_machine_future(start, transitions(Parsed))]
+    Input(&'static str),
+
+    #[state_machine_future(ready)]
+    Parsed(u8),
+
+    #[state_machine_future(error)]
+    Invalid(String),
+}
+
+impl PollParse for Parse {
+    fn poll_input<'a>(input: &'a mut RentToOwn<'a, Input>) -> Poll<AfterInput, String> {
+        match input.0.parse() {
+            Ok(n) => transition!(Parsed(n)),
+            Err(_) => Err(format!("not a number: {}", input.0)),
+        }
+    }
+}
+
+#[test]
+fn maps_error_into_another_enum() {
+    let mut parsed: MapErr<ParseFuture, _> = Parse::start("12").map_machine_err(AppError::Parse);
+    assert_eq!(parsed.poll(), Ok(Async::Ready(12)));
+
+    let mut invalid = Parse::start("twelve").map_machine_err(AppError::Parse);
+    assert_eq!(
+        invalid.poll(),
+        Err(AppError::Parse("not a number: twelve".to_string()))
+    );
+}
@@ -0,0 +1,59 @@
+//! Test that `max_size` accepts state machines that fit in their budget.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+use std::marker::PhantomData;
+use std::mem;
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(max_size = 64)]
+pub enum Small {
+    #[state_machine_future(start, transitions(Done))]
+    Start(u32),
+
+    #[state_machine_future(ready)]
+    Done(u32),
+
+    #[state_machine_future(error)]
+    Failed(()),
+}
+
+impl PollSmall for Small {
+    fn poll_start<'a>(start: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, ()> {
+        transition!(Done(start.0 + 1))
+    }
+}
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(max_size = "64")]
+pub enum Generic<T: 'static> {
+    #[state_machine_future(start, transitions(Finished))]
+    Begin(u32, PhantomData<T>),
+
+    #[state_machine_future(ready)]
+    Finished(u32),
+
+    #[state_machine_future(error)]
+    Broken(()),
+}
+
+impl<T: 'static> PollGeneric<T> for Generic<T> {
+    fn poll_begin<'a>(begin: &'a mut RentToOwn<'a, Begin<T>>) -> Poll<AfterBegin, ()> {
+        transition!(Finished(begin.0 + 1))
+    }
+}
+
+#[test]
+fn machine_within_max_size_runs() {
+    assert!(mem::size_of::<SmallFuture>() <= 64);
+    assert_eq!(Small::start(1).poll(), Ok(Async::Ready(2)));
+}
+
+#[test]
+fn generic_machine_within_max_size_runs() {
+    assert_eq!(Generic::<String>::start(1, PhantomData).poll(), Ok(Async::Ready(2)));
+}
@@ -0,0 +1,106 @@
+//! Test that `module` puts the generated items in a module of their own.
+
+#![allow(dead_code)]
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+#[derive(Debug)]
+pub struct Job {
+    steps: u32,
+}
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(module = "worker_internals", derive(Debug), current_state)]
+enum Worker {
+    #[state_machine_future(start, transitions(Working))]
+    Queued(Job),
+
+    #[state_machine_future(transitions(Working, Finished))]
+    Working(Job),
+
+    #[state_machine_future(ready)]
+    Finished(u32),
+
+    #[state_machine_future(error)]
+    Failed(()),
+}
+
+impl worker_internals::PollWorker for Worker {
+    fn poll_queued<'a>(
+        queued: &'a mut RentToOwn<'a, worker_internals::Queued>,
+    ) -> Poll<worker_internals::AfterQueued, ()> {
+        let worker_internals::Queued(job) = queued.take();
+        transition!(worker_internals::Working(job))
+    }
+
+    fn poll_working<'a>(
+        working: &'a mut RentToOwn<'a, worker_internals::Working>,
+    ) -> Poll<worker_internals::AfterWorking, ()> {
+        let worker_internals::Working(job) = working.take();
+        if job.steps == 0 {
+            transition!(worker_internals::Finished(3))
+        }
+        transition!(worker_internals::Working(Job {
+            steps: job.steps - 1,
+        }))
+    }
+}
+
+// The generated items are re-exported here, but the module's own items shadow
+// them.
+pub struct Queued;
+pub struct AfterWorking;
+
+mod shop {
+    use futures::Poll;
+    use state_machine_future::RentToOwn;
+
+    #[derive(StateMachineFuture)]
+    #[state_machine_future(module = "order_internals", current_state)]
+    pub enum Order {
+        #[state_machine_future(start, transitions(Packed))]
+        Placed(u32),
+
+        #[state_machine_future(ready)]
+        Packed(u32),
+
+        #[state_machine_future(error)]
+        Cancelled(()),
+    }
+
+    impl PollOrder for Order {
+        fn poll_placed<'a>(placed: &'a mut RentToOwn<'a, Placed>) -> Poll<AfterPlaced, ()> {
+            let Placed(items) = placed.take();
+            transition!(Packed(items))
+        }
+    }
+}
+
+#[test]
+fn generated_items_live_in_module() {
+    let mut worker: WorkerFuture = Worker::start(Job { steps: 2 });
+    assert_eq!(
+        worker.current_state(),
+        Some(worker_internals::WorkerState::Queued)
+    );
+    assert_eq!(worker.poll(), Ok(Async::Ready(3)));
+
+    let _ = (Queued, AfterWorking);
+}
+
+#[test]
+fn public_items_are_reexported_next_to_the_description() {
+    let after: shop::AfterPlaced = shop::Packed(2).into();
+    match after {
+        shop::AfterPlaced::Packed(shop::Packed(items)) => assert_eq!(items, 2),
+    }
+
+    let mut order: shop::OrderFuture = shop::Order::start(2);
+    assert_eq!(order.current_state(), Some(shop::OrderState::Placed));
+    assert_eq!(order.poll(), Ok(Async::Ready(2)));
+}
@@ -21,8 +21,10 @@ pub enum Fsm {
     RentToOwn,
 
     #[state_machine_future(ready)]
-    #[state_machine_future(error)]
     StateMachineFuture(()),
+
+    #[state_machine_future(error)]
+    Failed(()),
 }
 
 impl PollFsm for Fsm {
@@ -0,0 +1,43 @@
+//! Test that machines without an `error` state are infallible.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+use std::convert::Infallible;
+
+#[derive(StateMachineFuture)]
+pub enum Machine {
+    #[state_machine_future(start, transitions(Counting))]
+    Start(usize),
+
+    #[state_machine_future(transitions(Counting, Ready))]
+    Counting(usize),
+
+    #[state_machine_future(ready)]
+    Ready(usize),
+}
+
+impl PollMachine for Machine {
+    fn poll_start<'a>(start: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, Infallible> {
+        transition!(Counting(start.0))
+    }
+
+    fn poll_counting<'a>(
+        counting: &'a mut RentToOwn<'a, Counting>,
+    ) -> Poll<AfterCounting, Infallible> {
+        if counting.0 == 0 {
+            transition!(Ready(42))
+        }
+        transition!(Counting(counting.0 - 1))
+    }
+}
+
+#[test]
+fn no_error_state() {
+    let mut machine = Machine::start(3);
+    let result: Result<Async<usize>, Infallible> = machine.poll();
+    assert_eq!(result, Ok(Async::Ready(42)));
+}
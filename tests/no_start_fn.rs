@@ -0,0 +1,41 @@
+//! Test that `no_start_fn` leaves construction to a hand-written function,
+//! through the generated `From` impl for the start state.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(no_start_fn)]
+pub enum Lookup {
+    #[state_machine_future(start, transitions(Found))]
+    Query { key: String, attempts: u32 },
+
+    #[state_machine_future(ready)]
+    Found(usize),
+
+    #[state_machine_future(error)]
+    Missing(()),
+}
+
+impl PollLookup for Lookup {
+    fn poll_query<'a>(query: &'a mut RentToOwn<'a, Query>) -> Poll<AfterQuery, ()> {
+        transition!(Found(query.key.len() * query.attempts as usize))
+    }
+}
+
+/// The only way for users to get a `LookupFuture`.
+pub fn lookup(key: &str) -> LookupFuture {
+    LookupFuture::from(Query {
+        key: key.to_string(),
+        attempts: 2,
+    })
+}
+
+#[test]
+fn constructed_through_wrapper() {
+    assert_eq!(lookup("abc").poll(), Ok(Async::Ready(6)));
+}
@@ -0,0 +1,81 @@
+//! Test that `on_enter` hooks run when a transition enters their state, and
+//! that a fallible hook's error fails the state machine.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+pub enum Connection {
+    #[state_machine_future(start, transitions(Handshake))]
+    Dial(u32),
+
+    #[state_machine_future(on_enter, boxed, transitions(Open))]
+    Handshake(u32, Vec<&'static str>),
+
+    #[state_machine_future(on_enter(fallible), transitions(Closed))]
+    Open(u32, Vec<&'static str>),
+
+    #[state_machine_future(ready)]
+    Closed(Vec<&'static str>),
+
+    #[state_machine_future(error)]
+    Refused(String),
+}
+
+impl PollConnection for Connection {
+    fn poll_dial<'a>(dial: &'a mut RentToOwn<'a, Dial>) -> Poll<AfterDial, String> {
+        transition!(Handshake(dial.0, vec!["dial"]))
+    }
+
+    fn poll_handshake<'a>(
+        handshake: &'a mut RentToOwn<'a, Handshake>,
+    ) -> Poll<AfterHandshake, String> {
+        let Handshake(port, mut log) = handshake.take();
+        log.push("handshake");
+        transition!(Open(port, log))
+    }
+
+    fn poll_open<'a>(open: &'a mut RentToOwn<'a, Open>) -> Poll<AfterOpen, String> {
+        let Open(_, mut log) = open.take();
+        log.push("open");
+        transition!(Closed(log))
+    }
+
+    fn on_enter_handshake(handshake: &mut Handshake) {
+        handshake.1.push("enter handshake");
+    }
+
+    fn on_enter_open(open: &mut Open) -> Result<(), String> {
+        if open.0 == 0 {
+            return Err(format!("port {} refused", open.0));
+        }
+        open.1.push("enter open");
+        Ok(())
+    }
+}
+
+#[test]
+fn entry_hooks_run_in_order() {
+    assert_eq!(
+        Connection::start(80).poll(),
+        Ok(Async::Ready(vec![
+            "dial",
+            "enter handshake",
+            "handshake",
+            "enter open",
+            "open",
+        ]))
+    );
+}
+
+#[test]
+fn failing_entry_hook_ends_in_error_state() {
+    assert_eq!(
+        Connection::start(0).poll(),
+        Err("port 0 refused".to_string())
+    );
+}
@@ -0,0 +1,118 @@
+//! Test that `on_exit` hooks run when a state machine is dropped mid-run, and
+//! not when it completes.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+pub struct Upload {
+    log: Rc<RefCell<Vec<String>>>,
+    chunks: u32,
+}
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(reset, current_state)]
+pub enum Uploader {
+    #[state_machine_future(start, transitions(Sending), on_exit)]
+    Opening(Upload),
+
+    #[state_machine_future(transitions(Sending, Done), on_exit, boxed)]
+    Sending(Upload),
+
+    #[state_machine_future(ready)]
+    Done(u32),
+
+    #[state_machine_future(error)]
+    Failed(()),
+}
+
+impl PollUploader for Uploader {
+    fn poll_opening<'a>(opening: &'a mut RentToOwn<'a, Opening>) -> Poll<AfterOpening, ()> {
+        let Opening(upload) = opening.take();
+        transition!(Sending(upload))
+    }
+
+    fn poll_sending<'a>(sending: &'a mut RentToOwn<'a, Sending>) -> Poll<AfterSending, ()> {
+        if sending.0.chunks == 3 {
+            return Ok(Async::Ready(Done(3).into()));
+        }
+        sending.0.chunks += 1;
+        Ok(Async::NotReady)
+    }
+
+    fn on_exit_opening(opening: &mut Opening) {
+        opening.0.log.borrow_mut().push("cancelled while opening".to_string());
+    }
+
+    fn on_exit_sending(sending: &mut Sending) {
+        let message = format!("cancelled after {} chunks", sending.0.chunks);
+        sending.0.log.borrow_mut().push(message);
+    }
+}
+
+fn upload(log: &Rc<RefCell<Vec<String>>>) -> UploaderFuture {
+    Uploader::start(Upload {
+        log: log.clone(),
+        chunks: 0,
+    })
+}
+
+#[test]
+fn on_exit_runs_for_the_current_state_when_dropped() {
+    let log = Rc::new(RefCell::new(vec![]));
+
+    drop(upload(&log));
+
+    let mut uploader = upload(&log);
+    assert_eq!(uploader.poll(), Ok(Async::NotReady));
+    assert_eq!(uploader.poll(), Ok(Async::NotReady));
+    drop(uploader);
+
+    assert_eq!(
+        *log.borrow(),
+        vec!["cancelled while opening", "cancelled after 2 chunks"]
+    );
+}
+
+#[test]
+fn on_exit_does_not_run_on_completion() {
+    let log = Rc::new(RefCell::new(vec![]));
+
+    let mut uploader = upload(&log);
+    loop {
+        match uploader.poll() {
+            Ok(Async::NotReady) => continue,
+            done => {
+                assert_eq!(done, Ok(Async::Ready(3)));
+                break;
+            }
+        }
+    }
+    drop(uploader);
+
+    assert!(log.borrow().is_empty());
+}
+
+#[test]
+fn on_exit_runs_for_the_replaced_state_on_reset() {
+    let log = Rc::new(RefCell::new(vec![]));
+
+    let mut uploader = upload(&log);
+    assert_eq!(uploader.poll(), Ok(Async::NotReady));
+    uploader.reset(Upload {
+        log: log.clone(),
+        chunks: 0,
+    });
+    assert_eq!(*log.borrow(), vec!["cancelled after 1 chunks"]);
+
+    drop(uploader);
+    assert_eq!(
+        *log.borrow(),
+        vec!["cancelled after 1 chunks", "cancelled while opening"]
+    );
+}
@@ -0,0 +1,127 @@
+//! Test that the `on_transition` hook observes every successful transition, and
+//! entering the error state.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+use std::cell::RefCell;
+
+thread_local! {
+    static RECORDED: RefCell<Vec<String>> = const { RefCell::new(vec![]) };
+}
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(on_transition = "record")]
+pub enum Recorded {
+    #[state_machine_future(start, transitions(Middle))]
+    Start,
+
+    #[state_machine_future(transitions(Start, Ready))]
+    Middle(bool),
+
+    #[state_machine_future(ready)]
+    Ready(()),
+
+    #[state_machine_future(error)]
+    Error(()),
+}
+
+impl PollRecorded for Recorded {
+    fn poll_start<'a>(_: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, ()> {
+        let visited = RECORDED.with(|r| !r.borrow().is_empty());
+        transition!(Middle(visited))
+    }
+
+    fn poll_middle<'a>(middle: &'a mut RentToOwn<'a, Middle>) -> Poll<AfterMiddle, ()> {
+        if middle.0 {
+            transition!(Ready(()))
+        } else {
+            transition!(Start)
+        }
+    }
+
+    fn record(from: RecordedState, to: RecordedState) {
+        RECORDED.with(|r| r.borrow_mut().push(format!("{:?} -> {:?}", from, to)));
+    }
+}
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(on_transition = "record")]
+pub enum Unrecorded {
+    #[state_machine_future(start, transitions(Ready2))]
+    Start2,
+
+    #[state_machine_future(ready)]
+    Ready2(()),
+
+    #[state_machine_future(error)]
+    Error2(()),
+}
+
+impl PollUnrecorded for Unrecorded {
+    fn poll_start2<'a>(_: &'a mut RentToOwn<'a, Start2>) -> Poll<AfterStart2, ()> {
+        transition!(Ready2(()))
+    }
+}
+
+thread_local! {
+    static FAILURES: RefCell<Vec<String>> = const { RefCell::new(vec![]) };
+}
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(on_transition = "record")]
+pub enum Failing {
+    #[state_machine_future(start, transitions(Unreached))]
+    Trying,
+
+    #[state_machine_future(ready)]
+    Unreached(()),
+
+    #[state_machine_future(error)]
+    GaveUp(&'static str),
+}
+
+impl PollFailing for Failing {
+    fn poll_trying<'a>(_: &'a mut RentToOwn<'a, Trying>) -> Poll<AfterTrying, &'static str> {
+        Err("no luck")
+    }
+
+    fn record(from: FailingState, to: FailingState) {
+        FAILURES.with(|r| r.borrow_mut().push(format!("{:?} -> {:?}", from, to)));
+    }
+}
+
+#[test]
+fn on_transition_records_every_transition() {
+    let mut machine = Recorded::start();
+    assert_eq!(machine.poll(), Ok(Async::Ready(())));
+
+    let recorded = RECORDED.with(|r| r.borrow().clone());
+    assert_eq!(
+        recorded,
+        vec![
+            "Start -> Middle",
+            "Middle -> Start",
+            "Start -> Middle",
+            "Middle -> Ready",
+        ]
+    );
+}
+
+#[test]
+fn on_transition_defaults_to_no_op() {
+    let mut machine = Unrecorded::start();
+    assert_eq!(machine.poll(), Ok(Async::Ready(())));
+}
+
+#[test]
+fn on_transition_records_entering_the_error_state() {
+    let mut machine = Failing::start();
+    assert_eq!(machine.poll(), Err("no luck"));
+
+    let recorded = FAILURES.with(|r| r.borrow().clone());
+    assert_eq!(recorded, vec!["Trying -> GaveUp"]);
+}
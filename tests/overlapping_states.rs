@@ -4,6 +4,8 @@ extern crate futures;
 #[macro_use]
 extern crate state_machine_future;
 
+mod support;
+
 use futures::Poll;
 use state_machine_future::RentToOwn;
 
@@ -11,7 +13,6 @@ use state_machine_future::RentToOwn;
 pub enum AllOverlapping {
     #[state_machine_future(start)]
     #[state_machine_future(ready)]
-    #[state_machine_future(error)]
     OnlyState(()),
 }
 
@@ -32,19 +33,23 @@ impl PollNotOverlapping for NotOverlapping {
     }
 }
 
-#[derive(StateMachineFuture)]
+#[test]
+fn ready_error_overlapping_is_rejected() {
+    let description = "
 pub enum ReadyErrorOverlapping {
     #[state_machine_future(start)]
     #[state_machine_future(transitions(ReadyError))]
-    Init,
+    Init(()),
 
     #[state_machine_future(ready)]
     #[state_machine_future(error)]
     ReadyError(()),
 }
-
-impl PollReadyErrorOverlapping for ReadyErrorOverlapping {
-    fn poll_init<'a>(_: &'a mut RentToOwn<'a, Init>) -> Poll<AfterInit, ()> {
-        unimplemented!()
-    }
+";
+    support::assert_points_at(
+        "ready_error_overlapping",
+        description,
+        "ReadyError",
+        "The `ReadyError` state is marked both `ready` and `error`",
+    );
 }
@@ -0,0 +1,49 @@
+//! Test polling a state machine that is only held by mutable reference, as
+//! when it lives inside a larger struct.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(is_finished)]
+pub enum Ticker {
+    #[state_machine_future(start, transitions(Ticking, Rang))]
+    Ticking(u32),
+
+    #[state_machine_future(ready)]
+    Rang(&'static str),
+
+    #[state_machine_future(error)]
+    Broken(()),
+}
+
+impl PollTicker for Ticker {
+    fn poll_ticking<'a>(ticking: &'a mut RentToOwn<'a, Ticking>) -> Poll<AfterTicking, ()> {
+        match ticking.0 {
+            0 => transition!(Rang("ring")),
+            n => transition!(Ticking(n - 1)),
+        }
+    }
+}
+
+pub struct Alarm {
+    ticker: TickerFuture,
+}
+
+fn drive<F: Future>(mut future: F) -> Poll<F::Item, F::Error> {
+    future.poll()
+}
+
+#[test]
+fn poll_through_mut_ref() {
+    let mut alarm = Alarm {
+        ticker: Ticker::start(2),
+    };
+
+    assert_eq!(drive(&mut alarm.ticker), Ok(Async::Ready("ring")));
+    assert!(alarm.ticker.is_finished());
+}
@@ -0,0 +1,38 @@
+//! Test that `poll_fn` renames a state's polling trait method.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+pub enum Request {
+    #[state_machine_future(start, transitions(Receiving), poll_fn = "send")]
+    Sending(u32),
+
+    #[state_machine_future(transitions(Received))]
+    Receiving(u32),
+
+    #[state_machine_future(ready)]
+    Received(u32),
+
+    #[state_machine_future(error)]
+    Failed(()),
+}
+
+impl PollRequest for Request {
+    fn send<'a>(sending: &'a mut RentToOwn<'a, Sending>) -> Poll<AfterSending, ()> {
+        transition!(Receiving(sending.0 + 1))
+    }
+
+    fn poll_receiving<'a>(receiving: &'a mut RentToOwn<'a, Receiving>) -> Poll<AfterReceiving, ()> {
+        transition!(Received(receiving.0 * 2))
+    }
+}
+
+#[test]
+fn renamed_poll_method() {
+    assert_eq!(Request::start(1).poll(), Ok(Async::Ready(4)));
+}
@@ -0,0 +1,74 @@
+//! Test advancing a state machine one state at a time with `poll_one`.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(poll_one, current_state)]
+pub enum Pipeline {
+    #[state_machine_future(start, transitions(Building))]
+    Fetching(u32),
+
+    #[state_machine_future(transitions(Testing))]
+    Building(u32),
+
+    #[state_machine_future(transitions(Passed))]
+    Testing(u32),
+
+    #[state_machine_future(ready)]
+    Passed(u32),
+
+    #[state_machine_future(error)]
+    Broken(()),
+}
+
+impl PollPipeline for Pipeline {
+    fn poll_fetching<'a>(fetching: &'a mut RentToOwn<'a, Fetching>) -> Poll<AfterFetching, ()> {
+        transition!(Building(fetching.0 + 1))
+    }
+
+    fn poll_building<'a>(building: &'a mut RentToOwn<'a, Building>) -> Poll<AfterBuilding, ()> {
+        if building.0 < 2 {
+            building.0 += 1;
+            return Ok(Async::NotReady);
+        }
+        transition!(Testing(building.0 * 10))
+    }
+
+    fn poll_testing<'a>(testing: &'a mut RentToOwn<'a, Testing>) -> Poll<AfterTesting, ()> {
+        transition!(Passed(testing.0 + 1))
+    }
+}
+
+#[test]
+fn poll_one_advances_one_state_at_a_time() {
+    let mut pipeline = Pipeline::start(0);
+
+    assert_eq!(pipeline.poll_one(), Ok(Async::NotReady));
+    assert_eq!(pipeline.current_state(), Some(PipelineState::Building));
+
+    assert_eq!(pipeline.poll_one(), Ok(Async::NotReady));
+    assert_eq!(pipeline.current_state(), Some(PipelineState::Building));
+
+    assert_eq!(pipeline.poll_one(), Ok(Async::NotReady));
+    assert_eq!(pipeline.current_state(), Some(PipelineState::Testing));
+
+    assert_eq!(pipeline.poll_one(), Ok(Async::NotReady));
+    assert_eq!(pipeline.current_state(), Some(PipelineState::Passed));
+
+    assert_eq!(pipeline.poll_one(), Ok(Async::Ready(21)));
+    assert_eq!(pipeline.current_state(), None);
+}
+
+#[test]
+fn poll_one_and_poll_agree() {
+    let mut pipeline = Pipeline::start(0);
+
+    assert_eq!(pipeline.poll_one(), Ok(Async::NotReady));
+    assert_eq!(pipeline.poll(), Ok(Async::NotReady));
+    assert_eq!(pipeline.poll(), Ok(Async::Ready(21)));
+}
@@ -0,0 +1,40 @@
+//! Test that `poll_trait_bound` makes the bounds supertraits of the poll trait.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(poll_trait_bound = "Send + 'static")]
+pub enum Job {
+    #[state_machine_future(start, transitions(Finished))]
+    Queued(u32),
+
+    #[state_machine_future(ready)]
+    Finished(u32),
+
+    #[state_machine_future(error)]
+    Failed(()),
+}
+
+impl PollJob for Job {
+    fn poll_queued<'a>(queued: &'a mut RentToOwn<'a, Queued>) -> Poll<AfterQueued, ()> {
+        transition!(Finished(queued.0 * 2))
+    }
+}
+
+fn assert_send<T: Send>() {}
+
+// Only compiles because `Send` is a supertrait of `PollJob`.
+fn assert_implementor_send<T: PollJob>() {
+    assert_send::<T>();
+}
+
+#[test]
+fn poll_trait_implementors_are_send() {
+    assert_implementor_send::<Job>();
+    assert_eq!(Job::start(21).poll(), Ok(Async::Ready(42)));
+}
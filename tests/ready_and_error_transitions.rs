@@ -0,0 +1,120 @@
+//! Test that, with `warn_ready_and_error`, listing both the ready and error
+//! states in `transitions` warns at that state, and that nothing else does.
+
+#![deny(deprecated)]
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+mod support;
+
+use futures::Poll;
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(warn_ready_and_error)]
+pub enum Separate {
+    #[state_machine_future(start, transitions(Middle, Failed))]
+    Begin,
+
+    #[state_machine_future(transitions(Finished))]
+    Middle,
+
+    #[state_machine_future(ready)]
+    Finished(()),
+
+    #[state_machine_future(error)]
+    Failed(()),
+}
+
+impl PollSeparate for Separate {
+    fn poll_begin<'a>(_: &'a mut RentToOwn<'a, Begin>) -> Poll<AfterBegin, ()> {
+        unimplemented!()
+    }
+
+    fn poll_middle<'a>(_: &'a mut RentToOwn<'a, Middle>) -> Poll<AfterMiddle, ()> {
+        unimplemented!()
+    }
+}
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(warn_ready_and_error)]
+pub enum Silenced {
+    #[state_machine_future(start, transitions(Ready, Error))]
+    #[allow(deprecated)]
+    Start,
+
+    #[state_machine_future(ready)]
+    Ready(()),
+
+    #[state_machine_future(error)]
+    Error(()),
+}
+
+impl PollSilenced for Silenced {
+    fn poll_start<'a>(_: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, ()> {
+        unimplemented!()
+    }
+}
+
+#[derive(StateMachineFuture)]
+pub enum NotAskedFor {
+    #[state_machine_future(start, transitions(Done, Failure))]
+    Starting,
+
+    #[state_machine_future(ready)]
+    Done(()),
+
+    #[state_machine_future(error)]
+    Failure(()),
+}
+
+impl PollNotAskedFor for NotAskedFor {
+    fn poll_starting<'a>(_: &'a mut RentToOwn<'a, Starting>) -> Poll<AfterStarting, ()> {
+        unimplemented!()
+    }
+}
+
+#[test]
+fn only_ready_and_error_transitions_warn() {
+    // The `deny(deprecated)` above is the real test: this only compiles if
+    // none of these state machines warn.
+}
+
+#[test]
+fn ready_and_error_transitions_warn_at_the_state() {
+    let description = "
+#[state_machine_future(warn_ready_and_error)]
+pub enum Machine {
+    #[state_machine_future(start, transitions(Check))]
+    Start(()),
+
+    #[state_machine_future(transitions(Ready, Error))]
+    Check(()),
+
+    #[state_machine_future(ready)]
+    Ready(()),
+
+    #[state_machine_future(error)]
+    Error(()),
+}
+
+impl PollMachine for Machine {
+    fn poll_start<'a>(_: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, ()> {
+        transition!(Check(()))
+    }
+
+    fn poll_check<'a>(_: &'a mut RentToOwn<'a, Check>) -> Poll<AfterCheck, ()> {
+        transition!(Ready(()))
+    }
+}
+";
+    support::assert_points_at(
+        "ready_and_error_transitions",
+        description,
+        "Check",
+        "The `Check` state lists both the ready state `Ready` and the error state `Error` \
+         in its `transitions`",
+    );
+}
@@ -0,0 +1,63 @@
+//! Test that `ready_bound` accepts ready items that meet the bound.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+use std::thread;
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(ready_bound = "Send + 'static")]
+pub enum Fetch {
+    #[state_machine_future(start, transitions(Fetched))]
+    Requested(String),
+
+    #[state_machine_future(ready)]
+    Fetched(Vec<u8>),
+
+    #[state_machine_future(error)]
+    Failed(()),
+}
+
+impl PollFetch for Fetch {
+    fn poll_requested<'a>(requested: &'a mut RentToOwn<'a, Requested>) -> Poll<AfterRequested, ()> {
+        let requested = requested.take();
+        transition!(Fetched(requested.0.into_bytes()))
+    }
+}
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(ready_bound = "Send")]
+pub enum Generic<T: Send + 'static> {
+    #[state_machine_future(start, transitions(Done))]
+    Begin(T),
+
+    #[state_machine_future(ready)]
+    Done(T),
+
+    #[state_machine_future(error)]
+    Broken(()),
+}
+
+impl<T: Send + 'static> PollGeneric<T> for Generic<T> {
+    fn poll_begin<'a>(begin: &'a mut RentToOwn<'a, Begin<T>>) -> Poll<AfterBegin<T>, ()> {
+        transition!(Done(begin.take().0))
+    }
+}
+
+#[test]
+fn ready_item_can_be_sent_to_another_thread() {
+    let item = match Fetch::start("abc".to_string()).poll() {
+        Ok(Async::Ready(item)) => item,
+        other => panic!("expected ready, got {:?}", other),
+    };
+    let len = thread::spawn(move || item.len()).join().unwrap();
+    assert_eq!(len, 3);
+}
+
+#[test]
+fn generic_ready_item_meets_bound() {
+    assert_eq!(Generic::start(5u8).poll(), Ok(Async::Ready(5)));
+}
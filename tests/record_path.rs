@@ -0,0 +1,59 @@
+//! Test that `record_path` records every state entered, in order, including
+//! repeats.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(record_path, stall_info, reset)]
+pub enum Looping {
+    #[state_machine_future(start, transitions(Spin))]
+    Begin(u32),
+
+    #[state_machine_future(transitions(Spin, Landed))]
+    Spin(u32),
+
+    #[state_machine_future(ready)]
+    Landed(()),
+
+    #[state_machine_future(error)]
+    Crashed(()),
+}
+
+impl PollLooping for Looping {
+    fn poll_begin<'a>(begin: &'a mut RentToOwn<'a, Begin>) -> Poll<AfterBegin, ()> {
+        transition!(Spin(begin.0))
+    }
+
+    fn poll_spin<'a>(spin: &'a mut RentToOwn<'a, Spin>) -> Poll<AfterSpin, ()> {
+        match spin.0 {
+            0 => transition!(Landed(())),
+            100 => Err(()),
+            n => transition!(Spin(n - 1)),
+        }
+    }
+}
+
+fn names(path: &[LoopingState]) -> Vec<String> {
+    path.iter().map(|s| format!("{:?}", s)).collect()
+}
+
+#[test]
+fn path_preserves_order_and_repeats() {
+    let mut looping = Looping::start(2);
+    assert_eq!(names(looping.path()), vec!["Begin"]);
+
+    assert_eq!(looping.poll(), Ok(Async::Ready(())));
+    assert_eq!(
+        names(looping.path()),
+        vec!["Begin", "Spin", "Spin", "Spin", "Landed"]
+    );
+
+    looping.reset(100);
+    assert_eq!(looping.poll(), Err(()));
+    assert_eq!(names(looping.path()), vec!["Begin", "Spin", "Crashed"]);
+}
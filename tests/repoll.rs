@@ -0,0 +1,81 @@
+//! Test that a state with a `repoll` policy is polled again when it fails,
+//! until it succeeds or runs out of polls.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+pub struct Flaky {
+    failures_left: u32,
+    attempts: u32,
+}
+
+#[derive(StateMachineFuture)]
+pub enum Connection {
+    #[state_machine_future(start, transitions(Connected), repoll(max = 3))]
+    Connecting(Flaky),
+
+    #[state_machine_future(ready)]
+    Connected(u32),
+
+    #[state_machine_future(error)]
+    Failed(String),
+}
+
+impl PollConnection for Connection {
+    fn poll_connecting<'a>(
+        connecting: &'a mut RentToOwn<'a, Connecting>,
+    ) -> Poll<AfterConnecting, String> {
+        connecting.0.attempts += 1;
+        if connecting.0.failures_left > 0 {
+            connecting.0.failures_left -= 1;
+            return Err(format!("attempt {} failed", connecting.0.attempts));
+        }
+        transition!(Connected(connecting.0.attempts))
+    }
+}
+
+fn connect(failures: u32) -> Poll<u32, String> {
+    Connection::start(Flaky {
+        failures_left: failures,
+        attempts: 0,
+    }).poll()
+}
+
+#[test]
+fn succeeds_on_third_attempt() {
+    assert_eq!(connect(2), Ok(Async::Ready(3)));
+}
+
+#[test]
+fn fails_once_repolls_run_out() {
+    assert_eq!(connect(3), Ok(Async::Ready(4)));
+    assert_eq!(connect(4), Err("attempt 4 failed".to_string()));
+}
+
+#[derive(StateMachineFuture)]
+pub enum Consumed {
+    #[state_machine_future(start, transitions(Used), repoll(max = "5"))]
+    Unused(u32),
+
+    #[state_machine_future(ready)]
+    Used(()),
+
+    #[state_machine_future(error)]
+    Lost(u32),
+}
+
+impl PollConsumed for Consumed {
+    fn poll_unused<'a>(unused: &'a mut RentToOwn<'a, Unused>) -> Poll<AfterUnused, u32> {
+        let Unused(n) = unused.take();
+        Err(n)
+    }
+}
+
+#[test]
+fn taken_state_is_not_repolled() {
+    assert_eq!(Consumed::start(7).poll(), Err(7));
+}
@@ -0,0 +1,92 @@
+//! Test that `reset` rewinds a state machine back to a fresh start state, and
+//! that `is_finished` tracks whether it has run to completion.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(reset, is_finished)]
+pub enum Doubler {
+    #[state_machine_future(start, transitions(Doubled))]
+    Start(usize),
+
+    #[state_machine_future(ready)]
+    Doubled(usize),
+
+    #[state_machine_future(error)]
+    Error(()),
+}
+
+impl PollDoubler for Doubler {
+    fn poll_start<'a>(start: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, ()> {
+        transition!(Doubled(start.0 * 2))
+    }
+}
+
+#[test]
+fn reset_after_ready() {
+    let mut machine = Doubler::start(1);
+    assert_eq!(machine.poll(), Ok(Async::Ready(2)));
+
+    // A finished machine never resolves again...
+    assert_eq!(machine.poll(), Ok(Async::NotReady));
+
+    // ...until it is rewound with new start arguments.
+    machine.reset(21);
+    assert_eq!(machine.poll(), Ok(Async::Ready(42)));
+}
+
+#[test]
+fn is_finished_after_final_transition() {
+    let mut machine = Doubler::start(1);
+    assert!(!machine.is_finished());
+    assert_eq!(machine.poll(), Ok(Async::Ready(2)));
+    assert!(machine.is_finished());
+
+    machine.reset(2);
+    assert!(!machine.is_finished());
+}
+
+// Without the attributes, the generated `Future` leaves these names free.
+#[derive(StateMachineFuture)]
+pub enum Plain {
+    #[state_machine_future(start, transitions(PlainDone))]
+    PlainStart(usize),
+
+    #[state_machine_future(ready)]
+    PlainDone(usize),
+
+    #[state_machine_future(error)]
+    PlainError(()),
+}
+
+impl PollPlain for Plain {
+    fn poll_plain_start<'a>(
+        start: &'a mut RentToOwn<'a, PlainStart>,
+    ) -> Poll<AfterPlainStart, ()> {
+        transition!(PlainDone(start.0))
+    }
+}
+
+impl PlainFuture {
+    fn reset(&mut self) -> usize {
+        *self = Plain::start(0);
+        0
+    }
+
+    fn is_finished(&self) -> &'static str {
+        "not generated"
+    }
+}
+
+#[test]
+fn names_are_free_without_the_attributes() {
+    let mut machine = Plain::start(1);
+    assert_eq!(machine.reset(), 0);
+    assert_eq!(machine.is_finished(), "not generated");
+    assert_eq!(machine.poll(), Ok(Async::Ready(0)));
+}
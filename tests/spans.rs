@@ -0,0 +1,98 @@
+//! Test that errors about a single state point at that state's variant.
+//!
+//! `compile_fail` doctests can't check where an error points, so these build
+//! each description with `rustc` directly and look at the error's location.
+
+mod support;
+
+use support::assert_points_at;
+
+#[test]
+fn unknown_transition_points_at_its_state() {
+    // `Start` mentions `Middle` first, but the error is `Middle`'s.
+    let description = "
+pub enum Machine {
+    #[state_machine_future(start, transitions(Middle))]
+    Start(()),
+
+    #[state_machine_future(transitions(Nowhere))]
+    Middle(()),
+
+    #[state_machine_future(ready)]
+    Ready(()),
+}
+";
+    assert_points_at(
+        "unknown_transition",
+        description,
+        "Middle",
+        "Transition to unknown state `Nowhere` from state `Middle`",
+    );
+}
+
+#[test]
+fn malformed_ready_state_points_at_it() {
+    let description = "
+pub enum Machine {
+    #[state_machine_future(start, transitions(Ready))]
+    Start(()),
+
+    #[state_machine_future(ready)]
+    Ready((), ()),
+}
+";
+    assert_points_at(
+        "malformed_ready",
+        description,
+        "Ready",
+        "The `ready` state must only have one field",
+    );
+}
+
+#[test]
+fn second_error_state_is_pointed_at() {
+    let description = "
+pub enum Machine {
+    #[state_machine_future(start, transitions(Ready))]
+    Start(()),
+
+    #[state_machine_future(ready)]
+    Ready(()),
+
+    #[state_machine_future(error)]
+    Failed(()),
+
+    #[state_machine_future(error)]
+    Broken(()),
+}
+";
+    assert_points_at(
+        "second_error",
+        description,
+        "Broken",
+        "Only one state may be marked `error`, but `Failed` and `Broken` are both marked \
+         `error`; remove `error` from the extra state `Broken`",
+    );
+}
+
+#[test]
+fn unreachable_state_points_at_it() {
+    let description = "
+pub enum Machine {
+    #[state_machine_future(start, transitions(Ready))]
+    Start(()),
+
+    #[state_machine_future(transitions(Ready))]
+    Orphan(()),
+
+    #[state_machine_future(ready)]
+    Ready(()),
+}
+";
+    assert_points_at(
+        "unreachable",
+        description,
+        "Orphan",
+        "The `Orphan` state must be reachable",
+    );
+}
@@ -0,0 +1,67 @@
+//! Test that `stall_info` counts consecutive `NotReady` polls in one state.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(stall_info)]
+pub enum Waiter {
+    #[state_machine_future(start, transitions(Waiting))]
+    Begin(u32),
+
+    #[state_machine_future(transitions(Woken))]
+    Waiting(u32),
+
+    #[state_machine_future(ready)]
+    Woken(()),
+
+    #[state_machine_future(error)]
+    Failed(()),
+}
+
+impl PollWaiter for Waiter {
+    fn poll_begin<'a>(begin: &'a mut RentToOwn<'a, Begin>) -> Poll<AfterBegin, ()> {
+        transition!(Waiting(begin.0))
+    }
+
+    fn poll_waiting<'a>(waiting: &'a mut RentToOwn<'a, Waiting>) -> Poll<AfterWaiting, ()> {
+        if waiting.0 == 0 {
+            transition!(Woken(()))
+        }
+        waiting.0 -= 1;
+        Ok(Async::NotReady)
+    }
+}
+
+fn stalled_polls(waiter: &WaiterFuture) -> Option<u32> {
+    match waiter.stall_info() {
+        Some((WaiterState::Waiting, polls)) => Some(polls),
+        Some((state, _)) => panic!("stalled in unexpected state {:?}", state),
+        None => None,
+    }
+}
+
+#[test]
+fn stall_counter_increments() {
+    let mut waiter = Waiter::start(u32::MAX);
+    assert_eq!(stalled_polls(&waiter), None);
+
+    for polls in 1..4 {
+        assert_eq!(waiter.poll(), Ok(Async::NotReady));
+        assert_eq!(stalled_polls(&waiter), Some(polls));
+    }
+}
+
+#[test]
+fn stall_clears_when_ready() {
+    let mut waiter = Waiter::start(1);
+    assert_eq!(waiter.poll(), Ok(Async::NotReady));
+    assert_eq!(stalled_polls(&waiter), Some(1));
+
+    assert_eq!(waiter.poll(), Ok(Async::Ready(())));
+    assert_eq!(stalled_polls(&waiter), None);
+}
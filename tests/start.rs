@@ -1,7 +1,6 @@
 //! Test that the appropriat start method is generated for the original enum.
 
 extern crate futures;
-#[macro_use]
 extern crate state_machine_future;
 
 use futures::Poll;
@@ -14,8 +13,10 @@ pub enum Fsm {
     Begin(usize, bool),
 
     #[state_machine_future(ready)]
-    #[state_machine_future(error)]
     Done(()),
+
+    #[state_machine_future(error)]
+    Failed(()),
 }
 
 impl PollFsm for Fsm {
@@ -43,8 +44,10 @@ pub enum Fsm2 {
     Begin2 { x: bool, y: usize },
 
     #[state_machine_future(ready)]
-    #[state_machine_future(error)]
     Done2(()),
+
+    #[state_machine_future(error)]
+    Failed2(()),
 }
 
 impl PollFsm2 for Fsm2 {
@@ -72,8 +75,10 @@ pub enum Fsm3 {
     Begin3,
 
     #[state_machine_future(ready)]
-    #[state_machine_future(error)]
     Done3(()),
+
+    #[state_machine_future(error)]
+    Failed3(()),
 }
 
 impl PollFsm3 for Fsm3 {
@@ -0,0 +1,42 @@
+//! Test that, without `no_start_loops`, states may transition back to the
+//! start state.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+pub enum Retrying {
+    #[state_machine_future(start, transitions(Check))]
+    Attempt(u32),
+
+    #[state_machine_future(transitions(Attempt, Succeeded))]
+    Check(u32),
+
+    #[state_machine_future(ready)]
+    Succeeded(u32),
+
+    #[state_machine_future(error)]
+    GaveUp(()),
+}
+
+impl PollRetrying for Retrying {
+    fn poll_attempt<'a>(attempt: &'a mut RentToOwn<'a, Attempt>) -> Poll<AfterAttempt, ()> {
+        transition!(Check(attempt.0 + 1))
+    }
+
+    fn poll_check<'a>(check: &'a mut RentToOwn<'a, Check>) -> Poll<AfterCheck, ()> {
+        if check.0 < 3 {
+            transition!(Attempt(check.0))
+        }
+        transition!(Succeeded(check.0))
+    }
+}
+
+#[test]
+fn loops_back_to_start() {
+    assert_eq!(Retrying::start(0).poll(), Ok(Async::Ready(3)));
+}
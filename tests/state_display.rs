@@ -0,0 +1,50 @@
+//! Test that the mirror state enum displays each state's name as written.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::Poll;
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(state_enum)]
+pub enum Link {
+    #[state_machine_future(start, transitions(Connecting))]
+    Idle,
+
+    #[state_machine_future(transitions(TLSHandshake))]
+    Connecting,
+
+    #[state_machine_future(transitions(Up))]
+    TLSHandshake,
+
+    #[state_machine_future(ready)]
+    Up(()),
+
+    #[state_machine_future(error)]
+    Down(()),
+}
+
+impl PollLink for Link {
+    fn poll_idle<'a>(_: &'a mut RentToOwn<'a, Idle>) -> Poll<AfterIdle, ()> {
+        transition!(Connecting)
+    }
+
+    fn poll_connecting<'a>(_: &'a mut RentToOwn<'a, Connecting>) -> Poll<AfterConnecting, ()> {
+        transition!(TLSHandshake)
+    }
+
+    fn poll_tls_handshake<'a>(
+        _: &'a mut RentToOwn<'a, TLSHandshake>,
+    ) -> Poll<AfterTLSHandshake, ()> {
+        transition!(Up(()))
+    }
+}
+
+#[test]
+fn display_uses_source_spelling() {
+    assert_eq!(format!("{}", LinkState::Connecting), "Connecting");
+    assert_eq!(LinkState::TLSHandshake.to_string(), "TLSHandshake");
+    assert_eq!(format!("{:>4}|", LinkState::Up), "  Up|");
+}
@@ -0,0 +1,35 @@
+//! Test that an `#[allow(...)]` on one state applies to that state alone.
+
+#![deny(non_snake_case)]
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+pub enum Lints {
+    #[state_machine_future(start, transitions(Ready))]
+    #[allow(non_snake_case)]
+    Start { ShoutyField: usize },
+
+    #[state_machine_future(ready)]
+    Ready(usize),
+
+    #[state_machine_future(error)]
+    Error(()),
+}
+
+impl PollLints for Lints {
+    fn poll_start<'a>(start: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, ()> {
+        transition!(Ready(start.ShoutyField))
+    }
+}
+
+#[test]
+fn allowed_state_lint() {
+    let mut machine = Lints::start(3);
+    assert_eq!(machine.poll(), Ok(Async::Ready(3)));
+}
@@ -0,0 +1,43 @@
+//! Test that `repr` gives the mirror state enum declaration-order
+//! discriminants.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::Poll;
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(repr = "u8")]
+pub enum Machine {
+    #[state_machine_future(start, transitions(Middle))]
+    Begin(()),
+
+    #[state_machine_future(transitions(Done))]
+    Middle(()),
+
+    #[state_machine_future(ready)]
+    Done(()),
+
+    #[state_machine_future(error)]
+    Failed(()),
+}
+
+impl PollMachine for Machine {
+    fn poll_begin<'a>(_: &'a mut RentToOwn<'a, Begin>) -> Poll<AfterBegin, ()> {
+        transition!(Middle(()))
+    }
+
+    fn poll_middle<'a>(_: &'a mut RentToOwn<'a, Middle>) -> Poll<AfterMiddle, ()> {
+        transition!(Done(()))
+    }
+}
+
+#[test]
+fn discriminants_follow_declaration_order() {
+    assert_eq!(::std::mem::size_of::<MachineState>(), 1);
+    assert_eq!(MachineState::Begin as u8, 0);
+    assert_eq!(MachineState::Done as u8, 2);
+    assert_eq!(MachineState::Failed as u8, 3);
+}
@@ -0,0 +1,57 @@
+//! Test that a `stay` state can mutate itself in place and be polled again,
+//! without a transition.
+
+#![deny(deprecated)]
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(derive(Debug, PartialEq), on_transition = "record", transitions_const)]
+pub enum Accumulator {
+    #[state_machine_future(start, stay, transitions(Full))]
+    Filling(Vec<u32>),
+
+    #[state_machine_future(ready)]
+    Full(Vec<u32>),
+
+    #[state_machine_future(error)]
+    Spilled(()),
+}
+
+static TRANSITIONS: AtomicUsize = AtomicUsize::new(0);
+
+impl PollAccumulator for Accumulator {
+    fn poll_filling<'a>(filling: &'a mut RentToOwn<'a, Filling>) -> Poll<AfterFilling, ()> {
+        if filling.0.len() < 3 {
+            let next = filling.0.len() as u32;
+            filling.0.push(next);
+            return Ok(Async::Ready(AfterFilling::Stay));
+        }
+
+        let Filling(items) = filling.take();
+        transition!(Full(items))
+    }
+
+    fn record(_: AccumulatorState, _: AccumulatorState) {
+        TRANSITIONS.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn stay_polls_the_same_state_again() {
+    let mut accumulator = Accumulator::start(vec![]);
+
+    assert_eq!(accumulator.poll(), Ok(Async::Ready(vec![0, 1, 2])));
+    assert_eq!(TRANSITIONS.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn stay_is_an_after_variant() {
+    assert_ne!(AfterFilling::Stay, Full(vec![]).into());
+}
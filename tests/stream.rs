@@ -0,0 +1,52 @@
+//! Test that `stream` state machines yield items from their `yields` states.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Future, Poll, Stream};
+use state_machine_future::{RentToOwn, StateMachineStream};
+use std::convert::Infallible;
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(stream)]
+pub enum Tokens {
+    #[state_machine_future(start, transitions(Token, Done))]
+    Scanning(&'static str),
+
+    #[state_machine_future(yields, transitions(Scanning))]
+    Token(char, Scanning),
+
+    #[state_machine_future(ready)]
+    Done(()),
+}
+
+impl PollTokens for Tokens {
+    fn poll_scanning<'a>(
+        scanning: &'a mut RentToOwn<'a, Scanning>,
+    ) -> Poll<AfterScanning, Infallible> {
+        let mut chars = scanning.0.chars();
+        match chars.next() {
+            Some(c) => transition!(Token(c, Scanning(chars.as_str()))),
+            None => transition!(Done(())),
+        }
+    }
+}
+
+#[test]
+fn collects_yielded_items() {
+    let tokens = Tokens::start("abc").collect().wait();
+    assert_eq!(tokens, Ok(vec!['a', 'b', 'c']));
+}
+
+#[test]
+fn implements_state_machine_stream() {
+    fn check<S, F>(_: F)
+    where
+        S: StateMachineStream,
+        F: Fn(&'static str) -> S::Stream,
+    {
+    }
+
+    check::<Tokens, _>(Tokens::start);
+}
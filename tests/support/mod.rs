@@ -0,0 +1,160 @@
+//! Compiling state machine descriptions with `rustc`, and finding where their
+//! errors and warnings point.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// What each description is compiled after.
+const PRELUDE: &str = "#![allow(unused_imports)]
+
+#[macro_use]
+extern crate state_machine_future;
+extern crate futures;
+
+use futures::Poll;
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+";
+
+/// The features this test was built with, to build the library with the same.
+fn features() -> Vec<&'static str> {
+    let mut features = vec![];
+    if cfg!(feature = "rent_to_own") {
+        features.push("rent_to_own");
+    }
+    if cfg!(feature = "debug_code_generation") {
+        features.push("debug_code_generation");
+    }
+    features
+}
+
+/// The `.rlib` that Cargo built for each crate in `names`, as `(name, path)`.
+///
+/// This asks Cargo to build this package's library, with this test's profile
+/// and features, which it has already done for the test, so that the paths are
+/// exactly the libraries that this test is linked against.
+fn libraries(names: &[&str]) -> Vec<(String, PathBuf)> {
+    let mut cargo = Command::new(env::var("CARGO").unwrap_or_else(|_| "cargo".into()));
+    cargo
+        .arg("build")
+        .arg("--lib")
+        .arg("--message-format=json")
+        .arg("--manifest-path")
+        .arg(Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml"))
+        .arg("--no-default-features")
+        .arg(format!("--features={}", features().join(",")));
+    if !cfg!(debug_assertions) {
+        cargo.arg("--release");
+    }
+    let output = cargo.output().unwrap();
+    assert!(
+        output.status.success(),
+        "building the library failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    names
+        .iter()
+        .map(|name| {
+            let target = format!(
+                "\"target\":{{\"kind\":[\"lib\"],\"crate_types\":[\"lib\"],\"name\":\"{}\"",
+                name
+            );
+            let artifact = stdout
+                .lines()
+                .find(|line| {
+                    line.contains("\"reason\":\"compiler-artifact\"") && line.contains(&target)
+                })
+                .unwrap_or_else(|| panic!("Cargo should have built `{}`", name));
+            let filenames = &artifact[artifact.find("\"filenames\":[").unwrap()..];
+            // The `.rmeta` is always listed from `deps`, next to the `.rlib`
+            // and to the libraries it depends on.
+            let rmeta = filenames
+                .split('"')
+                .find(|file| file.ends_with(".rmeta"))
+                .unwrap_or_else(|| panic!("`{}` should have an `.rmeta`", name));
+            (name.to_string(), Path::new(rmeta).with_extension("rlib"))
+        })
+        .collect()
+}
+
+/// Compile `description`, with a `derive(StateMachineFuture)` on it, and
+/// return `rustc`'s diagnostics as `(line, column, message)`.
+fn diagnostics(name: &str, description: &str) -> Vec<(usize, usize, String)> {
+    let libraries = libraries(&["state_machine_future", "futures"]);
+    let dir = env::temp_dir().join(format!("smf-spans-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let source = dir.join("lib.rs");
+    fs::write(&source, format!("{}{}", PRELUDE, description)).unwrap();
+
+    let output = Command::new(env::var("RUSTC").unwrap_or_else(|_| "rustc".into()))
+        .arg("--crate-type=lib")
+        .arg("--emit=metadata")
+        .arg("--error-format=short")
+        .arg("--out-dir")
+        .arg(&dir)
+        .args(libraries.iter().flat_map(|(name, rlib)| {
+            vec![
+                "-L".to_string(),
+                format!("dependency={}", rlib.parent().unwrap().display()),
+                "--extern".to_string(),
+                format!("{}={}", name, rlib.display()),
+            ]
+        }))
+        .arg(&source)
+        .output()
+        .unwrap();
+    let _ = fs::remove_dir_all(&dir);
+
+    let path = source.to_string_lossy().into_owned();
+    String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .filter(|line| line.starts_with(&path))
+        .map(|line| {
+            let mut parts = line[path.len() + 1..].splitn(3, ':');
+            let line = parts.next().unwrap().parse().unwrap();
+            let column = parts.next().unwrap().parse().unwrap();
+            (line, column, parts.next().unwrap().trim().to_string())
+        })
+        .collect()
+}
+
+/// The line and column of `variant`'s declaration in `description`, as
+/// `diagnostics` numbers them.
+fn variant_location(description: &str, variant: &str) -> (usize, usize) {
+    let prelude_lines = PRELUDE.lines().count();
+    description
+        .lines()
+        .enumerate()
+        .find_map(|(i, line)| {
+            let column = line.find(&format!("{}(", variant))?;
+            if line[..column].trim().is_empty() {
+                Some((i + 1 + prelude_lines, column + 1))
+            } else {
+                None
+            }
+        })
+        .unwrap()
+}
+
+/// Assert that deriving `StateMachineFuture` for `description` gives an error
+/// or warning with `message`, pointing at `variant`.
+pub fn assert_points_at(name: &str, description: &str, variant: &str, message: &str) {
+    let diagnostics = diagnostics(name, description);
+    let location = diagnostics
+        .iter()
+        .find(|(_, _, diagnostic)| diagnostic.contains(message))
+        .map(|&(line, column, _)| (line, column))
+        .unwrap_or_else(|| panic!("{}: no `{}` in {:#?}", name, message, diagnostics));
+    assert_eq!(
+        location,
+        variant_location(description, variant),
+        "{}: the diagnostic should point at `{}`",
+        name,
+        variant
+    );
+}
@@ -0,0 +1,100 @@
+//! Test driving a `sync` state machine to completion with `step`.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Poll};
+use state_machine_future::{RentToOwn, StepResult};
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(sync, current_state)]
+pub enum Countdown {
+    #[state_machine_future(start, transitions(Counting))]
+    Armed(u32),
+
+    #[state_machine_future(transitions(Counting, Launched))]
+    Counting(u32),
+
+    #[state_machine_future(ready)]
+    Launched(&'static str),
+
+    #[state_machine_future(error)]
+    Aborted(String),
+}
+
+impl PollCountdown for Countdown {
+    fn poll_armed<'a>(armed: &'a mut RentToOwn<'a, Armed>) -> Poll<AfterArmed, String> {
+        let Armed(from) = armed.take();
+        transition!(Counting(from))
+    }
+
+    fn poll_counting<'a>(
+        counting: &'a mut RentToOwn<'a, Counting>,
+    ) -> Poll<AfterCounting, String> {
+        match counting.0 {
+            0 => transition!(Launched("liftoff")),
+            7 => Err("unlucky".to_string()),
+            n => transition!(Counting(n - 1)),
+        }
+    }
+}
+
+#[test]
+fn step_advances_one_transition_at_a_time() {
+    let mut countdown = Countdown::start(2);
+
+    assert_eq!(countdown.step(), StepResult::Pending);
+    assert_eq!(countdown.current_state(), Some(CountdownState::Counting));
+    assert_eq!(countdown.step(), StepResult::Pending);
+    assert_eq!(countdown.step(), StepResult::Pending);
+    assert_eq!(countdown.step(), StepResult::Done(Ok("liftoff")));
+    assert_eq!(countdown.current_state(), None);
+    assert_eq!(countdown.step(), StepResult::Pending);
+}
+
+#[test]
+fn step_reports_errors() {
+    let mut countdown = Countdown::start(8);
+
+    assert_eq!(countdown.step(), StepResult::Pending);
+    assert_eq!(countdown.step(), StepResult::Pending);
+    assert_eq!(
+        countdown.step(),
+        StepResult::Done(Err("unlucky".to_string()))
+    );
+}
+
+pub struct NotReadyOnce(bool);
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(sync, current_state)]
+pub enum Waits {
+    #[state_machine_future(start, transitions(Done))]
+    Waiting(NotReadyOnce),
+
+    #[state_machine_future(ready)]
+    Done(()),
+
+    #[state_machine_future(error)]
+    Failed(()),
+}
+
+impl PollWaits for Waits {
+    fn poll_waiting<'a>(waiting: &'a mut RentToOwn<'a, Waiting>) -> Poll<AfterWaiting, ()> {
+        if (waiting.0).0 {
+            transition!(Done(()))
+        }
+        (waiting.0).0 = true;
+        Ok(Async::NotReady)
+    }
+}
+
+#[test]
+fn not_ready_is_pending() {
+    let mut waits = Waits::start(NotReadyOnce(false));
+
+    assert_eq!(waits.step(), StepResult::Pending);
+    assert_eq!(waits.current_state(), Some(WaitsState::Waiting));
+    assert_eq!(waits.step(), StepResult::Done(Ok(())));
+}
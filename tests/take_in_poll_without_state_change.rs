@@ -14,8 +14,11 @@ pub enum Machine {
     #[state_machine_future(transitions(Ready))]
     Start,
 
-    #[state_machine_future(ready, error)]
+    #[state_machine_future(ready)]
     Ready(usize),
+
+    #[state_machine_future(error)]
+    Error(usize),
 }
 
 impl PollMachine for Machine {
@@ -0,0 +1,36 @@
+//! Test that generic parameters used only inside trait objects are kept.
+
+#![allow(bare_trait_objects)]
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+pub enum Produce<T: 'static> {
+    #[state_machine_future(start, transitions(Produced))]
+    Producing(Box<Fn() -> T + Send>),
+
+    #[state_machine_future(ready)]
+    Produced(T),
+
+    #[state_machine_future(error)]
+    Failed(()),
+}
+
+impl<T: 'static> PollProduce<T> for Produce<T> {
+    fn poll_producing<'a>(
+        producing: &'a mut RentToOwn<'a, Producing<T>>,
+    ) -> Poll<AfterProducing<T>, ()> {
+        transition!(Produced((producing.0)()))
+    }
+}
+
+#[test]
+fn trait_object_generics() {
+    let mut produce = Produce::start(Box::new(|| "made"));
+    assert_eq!(produce.poll(), Ok(Async::Ready("made")));
+}
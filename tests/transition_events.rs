@@ -0,0 +1,70 @@
+//! Test watching a state machine's progress with `transition_events`.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Poll, Stream};
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(transition_events)]
+pub enum Deploy {
+    #[state_machine_future(start, transitions(Building))]
+    Queued(u32),
+
+    #[state_machine_future(transitions(Building, Releasing, Failed))]
+    Building(u32),
+
+    #[state_machine_future(transitions(Released))]
+    Releasing(u32),
+
+    #[state_machine_future(ready)]
+    Released(u32),
+
+    #[state_machine_future(error)]
+    Failed(String),
+}
+
+impl PollDeploy for Deploy {
+    fn poll_queued<'a>(queued: &'a mut RentToOwn<'a, Queued>) -> Poll<AfterQueued, String> {
+        transition!(Building(queued.0))
+    }
+
+    fn poll_building<'a>(building: &'a mut RentToOwn<'a, Building>) -> Poll<AfterBuilding, String> {
+        match building.0 {
+            0 => transition!(Failed("nothing to build".to_string())),
+            1 => transition!(Releasing(1)),
+            n => transition!(Building(n - 1)),
+        }
+    }
+
+    fn poll_releasing<'a>(
+        releasing: &'a mut RentToOwn<'a, Releasing>,
+    ) -> Poll<AfterReleasing, String> {
+        transition!(Released(releasing.0))
+    }
+}
+
+#[test]
+fn events_follow_the_path_and_end_when_ready() {
+    let events: Vec<_> = Deploy::start(2).transition_events().wait().collect();
+    assert_eq!(
+        events,
+        vec![
+            Ok(DeployState::Building),
+            Ok(DeployState::Building),
+            Ok(DeployState::Releasing),
+            Ok(DeployState::Released),
+        ]
+    );
+}
+
+#[test]
+fn events_end_with_the_error() {
+    let mut events = Deploy::start(0).transition_events();
+    assert_eq!(events.poll(), Ok(Async::Ready(Some(DeployState::Building))));
+    assert_eq!(events.poll(), Ok(Async::Ready(Some(DeployState::Failed))));
+    assert_eq!(events.poll(), Err("nothing to build".to_string()));
+    assert_eq!(events.poll(), Ok(Async::Ready(None)));
+}
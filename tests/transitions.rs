@@ -20,8 +20,10 @@ pub enum Fsm {
     Middle(()),
 
     #[state_machine_future(ready)]
-    #[state_machine_future(error)]
     End(()),
+
+    #[state_machine_future(error)]
+    Failed(()),
 }
 
 pub fn check_begin_transitions(x: AfterBegin) {
@@ -0,0 +1,124 @@
+//! Test that the generated `TRANSITIONS` and `MERMAID` constants, and the
+//! `transitions_from` function, mirror the declared `transitions`.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::Poll;
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(transitions_const, transitions_from, mermaid)]
+pub enum Machine {
+    #[state_machine_future(start, transitions(Middle, Ready))]
+    Start,
+
+    #[state_machine_future(transitions(Start, Ready, Error))]
+    Middle,
+
+    #[state_machine_future(ready)]
+    Ready(()),
+
+    #[state_machine_future(error)]
+    Error(()),
+}
+
+impl PollMachine for Machine {
+    fn poll_start<'a>(_: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, ()> {
+        unimplemented!()
+    }
+
+    fn poll_middle<'a>(_: &'a mut RentToOwn<'a, Middle>) -> Poll<AfterMiddle, ()> {
+        unimplemented!()
+    }
+}
+
+#[test]
+fn transitions_const_matches_declaration() {
+    assert_eq!(
+        Machine::TRANSITIONS,
+        &[
+            ("Start", &["Middle", "Ready"][..]),
+            ("Middle", &["Start", "Ready", "Error"][..]),
+            ("Ready", &[][..]),
+            ("Error", &[][..]),
+        ][..]
+    );
+}
+
+#[test]
+fn transitions_from_matches_declaration() {
+    assert_eq!(
+        Machine::transitions_from(MachineState::Start),
+        &[MachineState::Middle, MachineState::Ready]
+    );
+    assert_eq!(
+        Machine::transitions_from(MachineState::Middle),
+        &[MachineState::Start, MachineState::Ready, MachineState::Error]
+    );
+    assert!(Machine::transitions_from(MachineState::Ready).is_empty());
+}
+
+#[test]
+fn mermaid_const_matches_declaration() {
+    assert_eq!(
+        Machine::MERMAID,
+        "stateDiagram-v2
+    [*] --> Start
+    Start --> Middle
+    Start --> Ready
+    Middle --> Start
+    Middle --> Ready
+    Middle --> Error
+    Ready --> [*]
+    Error --> [*]
+"
+    );
+}
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(mermaid, transitions_from)]
+pub enum Labeled {
+    #[state_machine_future(start, transitions(Retrying = "rare", Fetched = "common"))]
+    Fetching,
+
+    #[state_machine_future(transitions(Fetching, Fetched))]
+    Retrying,
+
+    #[state_machine_future(ready)]
+    Fetched(()),
+
+    #[state_machine_future(error)]
+    FetchFailed(()),
+}
+
+impl PollLabeled for Labeled {
+    fn poll_fetching<'a>(_: &'a mut RentToOwn<'a, Fetching>) -> Poll<AfterFetching, ()> {
+        unimplemented!()
+    }
+
+    fn poll_retrying<'a>(_: &'a mut RentToOwn<'a, Retrying>) -> Poll<AfterRetrying, ()> {
+        unimplemented!()
+    }
+}
+
+#[test]
+fn mermaid_const_labels_edges() {
+    assert_eq!(
+        Labeled::MERMAID,
+        "stateDiagram-v2
+    [*] --> Fetching
+    Fetching --> Retrying: rare
+    Fetching --> Fetched: common
+    Retrying --> Fetching
+    Retrying --> Fetched
+    Fetched --> [*]
+    FetchFailed --> [*]
+"
+    );
+    assert_eq!(
+        Labeled::transitions_from(LabeledState::Fetching),
+        &[LabeledState::Retrying, LabeledState::Fetched]
+    );
+}
@@ -0,0 +1,28 @@
+//! Test that a single state marked both `start` and `ready` makes a trivial
+//! state machine that resolves immediately.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future};
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(is_finished)]
+pub enum Trivial {
+    #[state_machine_future(start, ready)]
+    Done(usize),
+}
+
+impl PollTrivial for Trivial {}
+
+#[test]
+fn trivial_machine_resolves_immediately() {
+    let mut machine = Trivial::start(42);
+    assert_eq!(machine.poll(), Ok(Async::Ready(42)));
+}
+
+#[test]
+fn trivial_machine_starts_finished() {
+    assert!(Trivial::start(42).is_finished());
+}
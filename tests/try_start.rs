@@ -0,0 +1,53 @@
+//! Test that `validate_start` generates a `try_start` that checks the start
+//! state before constructing the future.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+#[derive(Debug, PartialEq)]
+pub struct OutOfRange(u8);
+
+#[derive(StateMachineFuture)]
+#[state_machine_future(validate_start = "check_percent")]
+pub enum Percent {
+    #[state_machine_future(start, transitions(Done))]
+    Start { percent: u8 },
+
+    #[state_machine_future(ready)]
+    Done(u8),
+
+    #[state_machine_future(error)]
+    Failed(OutOfRange),
+}
+
+impl PollPercent for Percent {
+    fn poll_start<'a>(start: &'a mut RentToOwn<'a, Start>) -> Poll<AfterStart, OutOfRange> {
+        transition!(Done(start.percent))
+    }
+
+    fn check_percent(start: &Start) -> Result<(), OutOfRange> {
+        if start.percent > 100 {
+            Err(OutOfRange(start.percent))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[test]
+fn try_start_accepts_valid_start_state() {
+    let mut machine = Percent::try_start(42).expect("42 is a valid percent");
+    assert_eq!(machine.poll(), Ok(Async::Ready(42)));
+}
+
+#[test]
+fn try_start_rejects_invalid_start_state() {
+    match Percent::try_start(101) {
+        Err(e) => assert_eq!(e, OutOfRange(101)),
+        Ok(_) => panic!("101 is not a valid percent"),
+    }
+}
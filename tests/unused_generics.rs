@@ -0,0 +1,112 @@
+//! Test that a type parameter that a state only needs because of another
+//! parameter's bounds is left out of that state's generics.
+
+extern crate futures;
+#[macro_use]
+extern crate state_machine_future;
+
+use futures::{Async, Future, Poll};
+use state_machine_future::RentToOwn;
+
+#[derive(StateMachineFuture)]
+pub enum Convert<T, U>
+where
+    U: Into<T>,
+{
+    #[state_machine_future(start, transitions(Converted))]
+    Start(U),
+
+    #[state_machine_future(ready)]
+    Converted(T),
+
+    #[state_machine_future(error)]
+    Error(()),
+}
+
+impl<T, U: Into<T>> PollConvert<T, U> for Convert<T, U> {
+    fn poll_start<'a>(start: &'a mut RentToOwn<'a, Start<U>>) -> Poll<AfterStart<T>, ()> {
+        let Start(u) = start.take();
+        transition!(Converted(u.into()))
+    }
+}
+
+#[derive(StateMachineFuture)]
+pub enum InlineBound<'a, T, U: Into<T> + 'a> {
+    #[state_machine_future(start, transitions(Done))]
+    Begin(&'a u8, U),
+
+    #[state_machine_future(ready)]
+    Done(T),
+
+    #[state_machine_future(error)]
+    Failed(()),
+}
+
+impl<'a, T, U: Into<T> + 'a> PollInlineBound<'a, T, U> for InlineBound<'a, T, U> {
+    fn poll_begin<'b>(
+        begin: &'b mut RentToOwn<'b, Begin<'a, U>>,
+    ) -> Poll<AfterBegin<T>, ()> {
+        let Begin(_, u) = begin.take();
+        transition!(Done(u.into()))
+    }
+}
+
+pub trait Source {
+    type Item;
+    fn next(&mut self) -> Self::Item;
+}
+
+pub struct Counter(u32);
+
+impl Source for Counter {
+    type Item = u32;
+    fn next(&mut self) -> u32 {
+        self.0 += 1;
+        self.0
+    }
+}
+
+// `E` only appears in `S`'s bound, so no state stores it; the generated
+// `Future` still compiles without a `PhantomData` marker for it.
+#[derive(StateMachineFuture)]
+pub enum BoundOnly<S: Source<Item = E>, E> {
+    #[state_machine_future(start, transitions(Pulled))]
+    Pulling(S),
+
+    #[state_machine_future(ready)]
+    Pulled(S),
+
+    #[state_machine_future(error)]
+    Dry(()),
+}
+
+impl<S: Source<Item = E>, E> PollBoundOnly<S, E> for BoundOnly<S, E> {
+    fn poll_pulling<'a>(
+        pulling: &'a mut RentToOwn<'a, Pulling<S, E>>,
+    ) -> Poll<AfterPulling<S, E>, ()> {
+        let Pulling(mut source) = pulling.take();
+        source.next();
+        transition!(Pulled(source))
+    }
+}
+
+#[test]
+fn where_clause_only_param() {
+    let mut machine = Convert::<u64, u32>::start(7);
+    assert_eq!(machine.poll(), Ok(Async::Ready(7u64)));
+}
+
+#[test]
+fn inline_bound_only_param() {
+    let mut machine = InlineBound::<u64, u32>::start(&0, 7);
+    assert_eq!(machine.poll(), Ok(Async::Ready(7u64)));
+}
+
+#[test]
+fn bound_only_param() {
+    let mut machine = BoundOnly::start(Counter(0));
+    match machine.poll() {
+        Ok(Async::Ready(Counter(n))) => assert_eq!(n, 1),
+        _ => panic!("expected the counter to be pulled once"),
+    }
+}
@@ -10,7 +10,6 @@ extern crate state_machine_future;
 pub enum Pub {
     #[state_machine_future(start)]
     #[state_machine_future(ready)]
-    #[state_machine_future(error)]
     PubState(()),
 }
 
@@ -18,7 +17,6 @@ pub enum Pub {
 pub(crate) enum PubCrate {
     #[state_machine_future(start)]
     #[state_machine_future(ready)]
-    #[state_machine_future(error)]
     PubCrateState(()),
 }
 
@@ -26,6 +24,46 @@ pub(crate) enum PubCrate {
 enum NonPub {
     #[state_machine_future(start)]
     #[state_machine_future(ready)]
-    #[state_machine_future(error)]
     NonPubState(()),
 }
+
+mod restricted {
+    use futures::Poll;
+    use state_machine_future::RentToOwn;
+
+    #[derive(StateMachineFuture)]
+    #[state_machine_future(current_state, all_states, transitions_const)]
+    pub(crate) enum Restricted {
+        #[state_machine_future(start, transitions(Finished))]
+        Begin(u32, u32),
+
+        #[state_machine_future(ready)]
+        Finished(u32),
+
+        #[state_machine_future(error)]
+        Failed(()),
+    }
+
+    impl PollRestricted for Restricted {
+        fn poll_begin<'a>(begin: &'a mut RentToOwn<'a, Begin>) -> Poll<AfterBegin, ()> {
+            let begin = begin.take();
+            transition!(Finished(begin.0 + begin.1))
+        }
+    }
+}
+
+#[test]
+fn pub_crate_items_are_usable_outside_their_module() {
+    use futures::{Async, Future};
+    use restricted::{AfterBegin, Begin, Restricted, RestrictedFuture, RestrictedState};
+
+    let begin = Begin(1, 2);
+    assert_eq!((begin.0, begin.1), (1, 2));
+    let _ = AfterBegin::into_finished(3);
+
+    let mut future: RestrictedFuture = Restricted::start(1, 2);
+    assert_eq!(future.current_state(), Some(RestrictedState::Begin));
+    assert_eq!(future.poll(), Ok(Async::Ready(3)));
+    assert_eq!(Restricted::all_states().count(), 3);
+    assert_eq!(Restricted::TRANSITIONS.len(), 3);
+}